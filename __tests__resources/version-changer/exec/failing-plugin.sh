@@ -0,0 +1,3 @@
+#!/bin/sh
+echo 'cannot set' >&2
+exit 3
@@ -0,0 +1,6 @@
+#!/bin/sh
+case "$1" in
+  get) cat "$2" ;;
+  set) printf '%s\n' "$2" > "$3" ;;
+  *) exit 2 ;;
+esac
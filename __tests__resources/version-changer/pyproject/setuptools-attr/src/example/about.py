@@ -0,0 +1,2 @@
+# version
+VERSION: str = '1.0.0'
@@ -0,0 +1,2 @@
+#define VERSION "1.0.0"
+// VERSION "1.0.0"
@@ -0,0 +1,11 @@
+Name:           example
+Version:        1.1.0
+Release:        3%{?dist}
+Summary:        Example package
+
+%description
+Version: 0.0.0 in description is not a tag
+
+%changelog
+* Mon Jan 01 2024 Jane Doe <jane@example.com> - 1.1.0-3
+- Fix packaging
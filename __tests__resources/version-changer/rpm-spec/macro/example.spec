@@ -0,0 +1,4 @@
+%global upstream_version 2.0.0
+Name:           example
+Version:        %{upstream_version}
+Release:        %{release_number}
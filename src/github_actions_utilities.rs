@@ -120,14 +120,7 @@ impl GithubActionsUtilities {
     pub async fn execute(self) -> CmdResult {
         use GithubActionsUtilities::*;
         match self {
-            GhSetOutput { name, value } => {
-                let value = value.get("value").await?;
-                if let Ok(path) = env::var("GITHUB_OUTPUT") {
-                    file_command(path.as_ref(), &key_value_message(&name, &value))
-                } else {
-                    issue_command("set-output", &[("name", name)], &value)
-                }
-            }
+            GhSetOutput { name, value } => set_output(name, &value.get("value").await?),
             GhExportVariable { name, value } => {
                 let value = value.get("value").await?;
                 if let Ok(path) = env::var("GITHUB_ENV") {
@@ -158,6 +151,15 @@ impl GithubActionsUtilities {
     }
 }
 
+/// Sets the output of the step with `GITHUB_OUTPUT` file or `set-output` command
+pub(crate) fn set_output(name: String, value: &str) -> CmdResult {
+    if let Ok(path) = env::var("GITHUB_OUTPUT") {
+        file_command(path.as_ref(), &key_value_message(&name, value))
+    } else {
+        issue_command("set-output", &[("name", name)], value)
+    }
+}
+
 fn issue_command(command: &str, options: &[(&str, String)], value: &str) -> CmdResult {
     let mut command_builder = String::from("::") + command;
 
@@ -1,6 +1,10 @@
+pub(crate) mod glob;
 pub(crate) mod gradle;
 pub(crate) mod json;
 pub(crate) mod properties;
+pub(crate) mod toml;
+pub(crate) mod xml;
+pub(crate) mod yaml;
 
 use crate::CmdResult;
 use std::fmt::{Display, Formatter};
@@ -0,0 +1,182 @@
+//! This module contains the minimal glob implementation for paths.
+//!
+//! `*` and `?` match in a path segment and `**` matches any number of segments.
+//! Wildcards don't match names starting with `.` like shells.
+
+use std::path::{Path, PathBuf};
+
+/// Returns whether the relative path separated with `/` matches the pattern
+pub(crate) fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = segments(pattern);
+    let path = segments(path);
+    matches_segments(&pattern, &path)
+}
+
+/// Returns the existing paths relative to `base` matching the pattern, separated with `/` and sorted
+pub(crate) fn expand(base: &Path, pattern: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    expand_segments(base, "", &segments(pattern), &mut found);
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Returns the existing paths matching the pattern which may be absolute.
+/// The pattern without wildcards is returned as is even if it doesn't exist.
+pub(crate) fn expand_path(pattern: &str) -> Vec<PathBuf> {
+    let parts = pattern.split('/').collect::<Vec<_>>();
+    let Some(wildcard) = parts.iter().position(|x| x.contains(['*', '?'])) else {
+        return vec![PathBuf::from(pattern)];
+    };
+    let base = match parts[..wildcard].join("/") {
+        base if !base.is_empty() => PathBuf::from(base),
+        _ if pattern.starts_with('/') => PathBuf::from("/"),
+        _ => PathBuf::new(),
+    };
+    let dir = if base.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        &base
+    };
+    expand(dir, &parts[wildcard..].join("/"))
+        .into_iter()
+        .map(|x| base.join(x))
+        .collect()
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|x| !x.is_empty() && *x != ".")
+        .collect()
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len())
+            .take_while(|&i| i == 0 || !path[i - 1].starts_with('.'))
+            .any(|i| matches_segments(rest, &path[i..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path)) => matches_name(segment, name) && matches_segments(rest, path),
+            None => false,
+        },
+    }
+}
+
+/// Matches a path segment with `*` and `?`
+fn matches_name(pattern: &str, name: &str) -> bool {
+    fn inner(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|i| inner(rest, &name[i..])),
+            Some(('?', rest)) => !name.is_empty() && inner(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && inner(rest, &name[1..]),
+        }
+    }
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    inner(&pattern, &name)
+}
+
+fn expand_segments(dir: &Path, relative: &str, pattern: &[&str], found: &mut Vec<String>) {
+    let join = |name: &str| {
+        if relative.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{relative}/{name}")
+        }
+    };
+
+    match pattern.split_first() {
+        None => found.push(relative.to_owned()),
+        Some((&"**", rest)) => {
+            expand_segments(dir, relative, rest, found);
+            for name in read_dir(dir, true) {
+                if !name.starts_with('.') {
+                    expand_segments(&dir.join(&name), &join(&name), pattern, found);
+                }
+            }
+        }
+        Some((segment, rest)) if !segment.contains(['*', '?']) => {
+            let path = dir.join(segment);
+            if path.exists() {
+                expand_segments(&path, &join(segment), rest, found);
+            }
+        }
+        Some((segment, rest)) => {
+            for name in read_dir(dir, !rest.is_empty()) {
+                if matches_name(segment, &name) {
+                    expand_segments(&dir.join(&name), &join(&name), rest, found);
+                }
+            }
+        }
+    }
+}
+
+fn read_dir(dir: &Path, only_dir: bool) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .map(|entry| entry.unwrap_or_else(|e| panic!("reading {}: {}", dir.display(), e)))
+        .filter(|entry| !only_dir || entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+#[test]
+fn matches_test() {
+    assert!(matches("packages/*", "packages/core"));
+    assert!(!matches("packages/*", "packages/core/sub"));
+    assert!(!matches("packages/*", "packages/.hidden"));
+    assert!(matches("packages/**", "packages/core/sub"));
+    assert!(matches("**/*.json", "manifest.json"));
+    assert!(matches("**/*.json", "a/b/manifest.json"));
+    assert!(matches("./src/v?.txt", "src/v1.txt"));
+    assert!(!matches("src/*.txt", "src/v1.json"));
+}
+
+#[test]
+fn expand_test() {
+    let dir = tempfile::tempdir().unwrap();
+    for path in [
+        "packages/a/x.json",
+        "packages/b/x.json",
+        "packages/b/c/x.json",
+        "x.json",
+    ] {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+    assert_eq!(
+        expand(dir.path(), "packages/*"),
+        ["packages/a", "packages/b"]
+    );
+    assert_eq!(
+        expand(dir.path(), "**/x.json"),
+        [
+            "packages/a/x.json",
+            "packages/b/c/x.json",
+            "packages/b/x.json",
+            "x.json"
+        ]
+    );
+    assert_eq!(expand(dir.path(), "packages/none"), Vec::<String>::new());
+
+    let base = dir.path().to_str().unwrap();
+    assert_eq!(
+        expand_path(&format!("{base}/packages/*/x.json")),
+        [
+            dir.path().join("packages/a/x.json"),
+            dir.path().join("packages/b/x.json")
+        ]
+    );
+    assert_eq!(
+        expand_path(&format!("{base}/none.json")),
+        [dir.path().join("none.json")]
+    );
+}
@@ -24,3 +24,49 @@ pub fn escape_groovy_string(s: &str) -> String {
         c@'\x00'..='\x1F' => format_args!("\\u{:04x}", c as u32),
     )
 }
+
+/// Unescapes the content of groovy or kotlin string literal without quotes.
+/// Returns None if the string is not a constant, which means it has invalid escape sequence
+/// or it has `$` interpolation and `interpolation` is true.
+pub fn unescape_groovy_string(s: &str, interpolation: bool) -> Option<String> {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.push(match chars.next()? {
+                'b' => '\x08',
+                'f' => '\x0C',
+                'n' => '\n',
+                'r' => '\r',
+                's' => ' ',
+                't' => '\t',
+                c @ ('\\' | '\'' | '"' | '$') => c,
+                'u' => {
+                    let hex = chars.as_str().get(..4)?;
+                    let c = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+                    chars.nth(3);
+                    c
+                }
+                _ => return None,
+            }),
+            '$' if interpolation => return None,
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+#[test]
+fn groovy_string_round_trip() {
+    for s in ["1.0.0", "1.0.0-SNAPSHOT", "a'b\"c\\d$e\nf\x01"] {
+        assert_eq!(
+            unescape_groovy_string(&escape_groovy_string(s), true).unwrap(),
+            s
+        );
+    }
+    assert_eq!(unescape_groovy_string("1.0.${build}", true), None);
+    assert_eq!(
+        unescape_groovy_string("1.0.${build}", false).unwrap(),
+        "1.0.${build}"
+    );
+}
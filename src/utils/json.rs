@@ -342,7 +342,7 @@ impl Display for JsonObject<'_> {
 }
 
 impl<'src> JsonObject<'src> {
-    pub(crate) fn set(&mut self, key_quoted: &'src str, value: Token<'src>) {
+    pub(crate) fn set(&mut self, key_quoted: &str, value: Token<'src>) {
         for member in &mut self.members {
             if member.key == Token::StringLiteral(key_quoted) {
                 member.value = JsonValue::Literal(value);
@@ -351,6 +351,13 @@ impl<'src> JsonObject<'src> {
         }
         panic!("there are no key {} in json object", key_quoted);
     }
+
+    pub(crate) fn get_mut(&mut self, key_quoted: &str) -> Option<&mut JsonValue<'src>> {
+        self.members
+            .iter_mut()
+            .find(|member| member.key == Token::StringLiteral(key_quoted))
+            .map(|member| &mut member.value)
+    }
 }
 
 pub(crate) struct JsonMember<'src> {
@@ -0,0 +1,46 @@
+//! Utilities to modify toml documents with toml_edit while preserving the original representation
+
+use toml_edit::Value;
+
+/// Replaces the string value with preserving decor and quote style of the original value
+pub(crate) fn set_string(value: &mut Value, new_value: &str) {
+    let literal = match value {
+        Value::String(s) => s
+            .as_repr()
+            .and_then(|repr| repr.as_raw().as_str())
+            .is_some_and(|raw| raw.starts_with('\'') && !raw.starts_with("'''")),
+        _ => false,
+    };
+
+    let mut replacement = if literal && !new_value.contains(['\'', '\n', '\r']) {
+        format!("'{new_value}'")
+            .parse::<Value>()
+            .expect("literal string is valid toml")
+    } else {
+        Value::from(new_value)
+    };
+
+    *replacement.decor_mut() = value.decor().clone();
+    *value = replacement;
+}
+
+#[test]
+fn set_string_test() {
+    let mut document = r#"
+basic = "1.0.0" # comment
+literal = '1.0.0'
+"#
+    .parse::<toml_edit::DocumentMut>()
+    .unwrap();
+
+    set_string(document["basic"].as_value_mut().unwrap(), "2.0.0");
+    set_string(document["literal"].as_value_mut().unwrap(), "2.0.0");
+
+    assert_eq!(
+        document.to_string(),
+        r#"
+basic = "2.0.0" # comment
+literal = '2.0.0'
+"#
+    );
+}
@@ -0,0 +1,426 @@
+//! This module contains the minimal XML parser with preserving original representation
+//!
+//! This parser only recognizes element structure and text content of elements.
+//! Modification is done by replacing range of the original source so everything else,
+//! including comments, whitespaces, and attributes are kept as is.
+
+use std::fmt::Display;
+use std::ops::Range;
+
+#[derive(Debug)]
+pub(crate) enum XmlError {
+    UnexpectedEof,
+    InvalidChar(usize),
+    MismatchedEndTag(usize),
+    NoRootElement,
+}
+
+impl Display for XmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XmlError::UnexpectedEof => f.write_str("unexpected end of file"),
+            XmlError::InvalidChar(pos) => write!(f, "invalid character at {}", pos),
+            XmlError::MismatchedEndTag(pos) => write!(f, "mismatched end tag at {}", pos),
+            XmlError::NoRootElement => f.write_str("no root element"),
+        }
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct ElementId(usize);
+
+#[derive(Debug)]
+struct Element {
+    name: Range<usize>,
+    /// range of the start tag including `<` and `>`
+    start_tag: Range<usize>,
+    /// range between start tag and end tag. None for empty element tag
+    content: Option<Range<usize>>,
+    children: Vec<ElementId>,
+}
+
+pub(crate) struct XmlDocument<'src> {
+    src: &'src str,
+    elements: Vec<Element>,
+    root: ElementId,
+    replacements: Vec<(Range<usize>, String)>,
+}
+
+impl<'src> XmlDocument<'src> {
+    pub fn parse(src: &'src str) -> Result<Self, XmlError> {
+        Parser {
+            src,
+            pos: 0,
+            elements: Vec::new(),
+        }
+        .parse()
+    }
+
+    pub fn name(&self, id: ElementId) -> &'src str {
+        &self.src[self.elements[id.0].name.clone()]
+    }
+
+    pub fn children(&self, id: ElementId) -> impl Iterator<Item = ElementId> + '_ {
+        self.elements[id.0].children.iter().copied()
+    }
+
+    pub fn child(&self, id: ElementId, name: &str) -> Option<ElementId> {
+        self.children(id).find(|&child| self.name(child) == name)
+    }
+
+    /// Finds the element with the path from the root element. the path doesn't include root element name.
+    pub fn find(&self, path: &[&str]) -> Option<ElementId> {
+        path.iter()
+            .try_fold(self.root, |element, name| self.child(element, name))
+    }
+
+    /// Returns the unescaped text content of the element.
+    /// Returns None if the element has child elements.
+    pub fn text(&self, id: ElementId) -> Option<String> {
+        let element = &self.elements[id.0];
+        if !element.children.is_empty() {
+            return None;
+        }
+        let Some(content) = element.content.clone() else {
+            return Some(String::new());
+        };
+        Some(unescape(&self.src[content]))
+    }
+
+    /// Replaces the content of the element with the text.
+    pub fn set_text(&mut self, id: ElementId, text: &str) {
+        let element = &self.elements[id.0];
+        let escaped = escape(text);
+        match element.content.clone() {
+            Some(content) => self.replace(content, escaped),
+            None => {
+                // <name/> => <name>text</name>
+                let start_tag = element.start_tag.clone();
+                let tag = self.src[start_tag.start..start_tag.end - 2].trim_end();
+                let name = self.name(id);
+                self.replace(start_tag, format!("{tag}>{escaped}</{name}>"));
+            }
+        }
+    }
+
+    /// Returns the whitespace before the start tag of the element
+    /// if the element is the first thing in the line
+    fn indent(&self, id: ElementId) -> Option<&'src str> {
+        let start = self.elements[id.0].start_tag.start;
+        let line_start = self.src[..start].rfind('\n').map(|x| x + 1).unwrap_or(0);
+        let indent = &self.src[line_start..start];
+        indent.chars().all(char::is_whitespace).then_some(indent)
+    }
+
+    /// Inserts a new element with text after the element, on the next line with same indent.
+    pub fn insert_after(&mut self, id: ElementId, name: &str, text: &str) {
+        let end = self.element_end(id);
+        let indent = self.indent(id).unwrap_or("");
+        let newline = if self.src.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let text = escape(text);
+        self.replace(
+            end..end,
+            format!("{newline}{indent}<{name}>{text}</{name}>"),
+        );
+    }
+
+    /// Removes the element. If the element is the only thing in the line, the line is removed.
+    pub fn remove(&mut self, id: ElementId) {
+        let mut start = self.elements[id.0].start_tag.start;
+        let mut end = self.element_end(id);
+        let line_rest = &self.src[end..];
+        let line_rest = &line_rest[..line_rest.find('\n').unwrap_or(line_rest.len())];
+        let line_rest = line_rest.trim_end_matches('\r');
+        if let Some(indent) = self.indent(id) {
+            let line_start = start - indent.len();
+            if line_start > 0 && line_rest.trim().is_empty() {
+                // remove the line with preceding newline
+                start = line_start - 1;
+                if self.src[..start].ends_with('\r') {
+                    start -= 1;
+                }
+                end += line_rest.len();
+            }
+        }
+        self.replace(start..end, String::new());
+    }
+
+    /// Returns the position after the end tag
+    fn element_end(&self, id: ElementId) -> usize {
+        let element = &self.elements[id.0];
+        match &element.content {
+            Some(content) => {
+                let end_tag = &self.src[content.end..];
+                content.end + end_tag.find('>').expect("end tag must be closed") + 1
+            }
+            None => element.start_tag.end,
+        }
+    }
+
+    fn replace(&mut self, range: Range<usize>, value: String) {
+        debug_assert!(
+            self.replacements
+                .iter()
+                .all(|(r, _)| r.end <= range.start || range.end <= r.start || r == &range),
+            "overlapping replacement"
+        );
+        if !range.is_empty() {
+            self.replacements.retain(|(r, _)| r != &range);
+        }
+        self.replacements.push((range, value));
+    }
+}
+
+impl Display for XmlDocument<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut replacements = self.replacements.iter().collect::<Vec<_>>();
+        replacements.sort_by_key(|(range, _)| (range.start, range.end));
+        let mut pos = 0;
+        for (range, value) in replacements {
+            f.write_str(&self.src[pos..range.start])?;
+            f.write_str(value)?;
+            pos = range.end;
+        }
+        f.write_str(&self.src[pos..])
+    }
+}
+
+struct Parser<'src> {
+    src: &'src str,
+    pos: usize,
+    elements: Vec<Element>,
+}
+
+impl<'src> Parser<'src> {
+    fn parse(mut self) -> Result<XmlDocument<'src>, XmlError> {
+        let mut stack = Vec::<ElementId>::new();
+        let mut root = None;
+
+        while let Some(offset) = self.src[self.pos..].find('<') {
+            let start = self.pos + offset;
+            let rest = &self.src[start..];
+            if rest.starts_with("<!--") {
+                self.pos = self.skip_until(start + 4, "-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos = self.skip_until(start + 9, "]]>")?;
+            } else if rest.starts_with("<?") {
+                self.pos = self.skip_until(start + 2, "?>")?;
+            } else if rest.starts_with("<!") {
+                self.pos = self.skip_doctype(start)?;
+            } else if rest.starts_with("</") {
+                let name = self.read_name(start + 2)?;
+                let end = self.skip_until(name.end, ">")?;
+                let Some(id) = stack.pop() else {
+                    return Err(XmlError::MismatchedEndTag(start));
+                };
+                let element = &mut self.elements[id.0];
+                if self.src[element.name.clone()] != self.src[name] {
+                    return Err(XmlError::MismatchedEndTag(start));
+                }
+                element.content = Some(element.start_tag.end..start);
+                self.pos = end;
+            } else {
+                let name = self.read_name(start + 1)?;
+                let end = self.skip_tag(name.end)?;
+                let empty = self.src[..end].ends_with("/>");
+                let id = ElementId(self.elements.len());
+                if let Some(parent) = stack.last() {
+                    self.elements[parent.0].children.push(id);
+                } else if root.is_some() {
+                    return Err(XmlError::InvalidChar(start));
+                } else {
+                    root = Some(id);
+                }
+                self.elements.push(Element {
+                    name,
+                    start_tag: start..end,
+                    content: None,
+                    children: Vec::new(),
+                });
+                if !empty {
+                    stack.push(id);
+                }
+                self.pos = end;
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(XmlError::UnexpectedEof);
+        }
+
+        Ok(XmlDocument {
+            src: self.src,
+            elements: self.elements,
+            root: root.ok_or(XmlError::NoRootElement)?,
+            replacements: Vec::new(),
+        })
+    }
+
+    /// returns the position after the terminator
+    fn skip_until(&self, from: usize, terminator: &str) -> Result<usize, XmlError> {
+        match self.src[from..].find(terminator) {
+            Some(offset) => Ok(from + offset + terminator.len()),
+            None => Err(XmlError::UnexpectedEof),
+        }
+    }
+
+    fn skip_doctype(&self, start: usize) -> Result<usize, XmlError> {
+        let mut depth = 0;
+        for (i, c) in self.src[start..].char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '>' if depth == 0 => return Ok(start + i + 1),
+                _ => {}
+            }
+        }
+        Err(XmlError::UnexpectedEof)
+    }
+
+    fn read_name(&self, start: usize) -> Result<Range<usize>, XmlError> {
+        let len = self.src[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '='))
+            .ok_or(XmlError::UnexpectedEof)?;
+        if len == 0 {
+            return Err(XmlError::InvalidChar(start));
+        }
+        Ok(start..start + len)
+    }
+
+    /// skips attributes and returns the position after `>`
+    fn skip_tag(&self, from: usize) -> Result<usize, XmlError> {
+        let mut quote = None;
+        for (i, c) in self.src[from..].char_indices() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), c) if q == c => quote = None,
+                (None, '>') => return Ok(from + i + 1),
+                (None, '<') => return Err(XmlError::InvalidChar(from + i)),
+                _ => {}
+            }
+        }
+        Err(XmlError::UnexpectedEof)
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(['&', '<']) {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            result.push_str(&cdata[..end]);
+            rest = cdata.get(end + 3..).unwrap_or("");
+        } else if rest.starts_with('<') {
+            // comment or processing instruction in the text
+            let end = if rest.starts_with("<!--") { "-->" } else { ">" };
+            let skip = rest.find(end).map(|x| x + end.len()).unwrap_or(rest.len());
+            rest = &rest[skip..];
+        } else {
+            // `&` without `;` is kept as is
+            let end = rest.find(';');
+            let entity = end.map_or("", |end| &rest[1..end]);
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "" => None,
+                _ => entity
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            match c {
+                Some(c) => {
+                    result.push(c);
+                    rest = &rest[end.unwrap() + 1..];
+                }
+                None => {
+                    // unknown entity: keep as is
+                    result.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    escapes!(text, '&' => "&amp;", '<' => "&lt;", '>' => "&gt;")
+}
+
+#[test]
+fn parse_and_modify() {
+    let src = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- comment with <tag> -->
+<project xmlns="http://maven.apache.org/POM/4.0.0" attr='a>b'>
+  <modelVersion>4.0.0</modelVersion>
+  <parent>
+    <version>1.0&#46;0</version>
+  </parent>
+  <version>1.0.0</version>
+  <empty/>
+  <name><![CDATA[a < b]]> &amp; c</name>
+</project>
+"#;
+    let mut document = XmlDocument::parse(src).unwrap();
+    let version = document.find(&["version"]).unwrap();
+    let parent_version = document.find(&["parent", "version"]).unwrap();
+    let empty = document.find(&["empty"]).unwrap();
+    let name = document.find(&["name"]).unwrap();
+    assert_eq!(document.text(version).unwrap(), "1.0.0");
+    assert_eq!(document.text(parent_version).unwrap(), "1.0.0");
+    assert_eq!(document.text(empty).unwrap(), "");
+    assert_eq!(document.text(name).unwrap(), "a < b & c");
+    assert_eq!(document.text(document.find(&["parent"]).unwrap()), None);
+
+    document.set_text(version, "2.0.0-<SNAPSHOT>");
+    document.set_text(empty, "value");
+    document.insert_after(version, "inserted", "text");
+    document.remove(document.find(&["modelVersion"]).unwrap());
+
+    assert_eq!(
+        document.to_string(),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- comment with <tag> -->
+<project xmlns="http://maven.apache.org/POM/4.0.0" attr='a>b'>
+  <parent>
+    <version>1.0&#46;0</version>
+  </parent>
+  <version>2.0.0-&lt;SNAPSHOT&gt;</version>
+  <inserted>text</inserted>
+  <empty>value</empty>
+  <name><![CDATA[a < b]]> &amp; c</name>
+</project>
+"#
+    );
+}
+
+#[test]
+fn mismatched_tag() {
+    assert!(matches!(
+        XmlDocument::parse("<a><b></a></b>"),
+        Err(XmlError::MismatchedEndTag(6))
+    ));
+}
+
+#[test]
+fn unescape_without_semicolon() {
+    assert_eq!(unescape("a &lt"), "a &lt");
+    assert_eq!(unescape("a & b &amp; c"), "a & b & c");
+    assert_eq!(unescape("&lt &gt;"), "&lt >");
+}
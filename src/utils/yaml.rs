@@ -0,0 +1,577 @@
+//! This module contains the minimal YAML parser with preserving original representation
+//!
+//! Only block mappings and block sequences are parsed into structure, and
+//! scalars are recognized only if they are in a single line.
+//! Other nodes like flow collections, block scalars, and aliases are kept as is and cannot be modified.
+//! Modification is done by replacing range of the original source so
+//! comments, anchors, quoting style, and key order are kept.
+
+use regex::Regex;
+use std::fmt::Display;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+#[derive(Debug)]
+pub(crate) enum YamlError {
+    /// the line (1-origin) is not supported by this parser
+    Unsupported(usize),
+}
+
+impl Display for YamlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YamlError::Unsupported(line) => write!(f, "unsupported yaml syntax at line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+#[derive(Debug)]
+pub(crate) enum YamlNode {
+    Mapping(Vec<(String, YamlNode)>),
+    Sequence(Vec<YamlNode>),
+    /// range of the single line scalar in the source including quotes
+    Scalar(Range<usize>),
+    /// empty node, flow collections, block scalars, multi line scalars, or aliases
+    Other,
+}
+
+impl YamlNode {
+    pub fn get(&self, key: &str) -> Option<&YamlNode> {
+        match self {
+            YamlNode::Mapping(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn items(&self) -> &[YamlNode] {
+        match self {
+            YamlNode::Sequence(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// Finds the node with path. numeric segment is used as an index for sequences.
+    pub fn find(&self, path: &[&str]) -> Option<&YamlNode> {
+        path.iter().try_fold(self, |node, segment| match node {
+            YamlNode::Sequence(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => node.get(segment),
+        })
+    }
+
+    pub fn as_scalar(&self) -> Option<Range<usize>> {
+        match self {
+            YamlNode::Scalar(range) => Some(range.clone()),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) struct YamlDocument<'src> {
+    src: &'src str,
+    documents: Vec<YamlNode>,
+    replacements: Vec<(Range<usize>, String)>,
+}
+
+impl<'src> YamlDocument<'src> {
+    pub fn parse(src: &'src str) -> Result<Self, YamlError> {
+        let mut documents = Vec::new();
+        let mut lines = Vec::new();
+        let mut has_content = false;
+
+        let mut offset = 0;
+        for (number, line) in src.split_inclusive('\n').enumerate() {
+            let start = offset;
+            offset += line.len();
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.starts_with("---") && line[3..].chars().next().is_none_or(|c| c == ' ') {
+                // document separator. the first separator is the start of the first document
+                if has_content || !lines.is_empty() {
+                    documents.push(parse_document(src, std::mem::take(&mut lines))?);
+                }
+                has_content = true;
+                continue;
+            }
+            if line.starts_with("...") || line.starts_with('%') {
+                // document end marker or directive
+                continue;
+            }
+
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            let end = content_end(line);
+            if end <= indent || line[indent..].starts_with('#') {
+                // blank or comment line
+                continue;
+            }
+            lines.push(Line {
+                number: number + 1,
+                indent,
+                start: start + indent,
+                end: start + end,
+            });
+        }
+        if has_content || !lines.is_empty() {
+            documents.push(parse_document(src, lines)?);
+        }
+
+        Ok(Self {
+            src,
+            documents,
+            replacements: Vec::new(),
+        })
+    }
+
+    pub fn documents(&self) -> &[YamlNode] {
+        &self.documents
+    }
+
+    /// Returns the root node of the first document
+    pub fn root(&self) -> &YamlNode {
+        self.documents.first().unwrap_or(&YamlNode::Other)
+    }
+
+    /// Returns unquoted value of the scalar
+    pub fn scalar(&self, range: Range<usize>) -> String {
+        unquote(&self.src[range])
+    }
+
+    /// Replaces the scalar with the value with preserving quoting style.
+    /// Plain scalar is quoted only if the value would be resolved to another type than the original,
+    /// like `1.10` replacing `1.0.0`
+    pub fn set_scalar(&mut self, range: Range<usize>, value: &str) {
+        let original = &self.src[range.clone()];
+        let quoted = match original.chars().next() {
+            Some('"') => crate::utils::json::quote_string(value),
+            Some('\'') => format!("'{}'", value.replace('\'', "''")),
+            _ if is_plain_safe(value)
+                && resolves_to_non_string(value) == resolves_to_non_string(original) =>
+            {
+                value.to_owned()
+            }
+            _ => crate::utils::json::quote_string(value),
+        };
+        self.replacements.retain(|(r, _)| r != &range);
+        self.replacements.push((range, quoted));
+    }
+
+    /// Replaces the scalar with plain scalar if possible regardless of the resolved type.
+    /// This is for files whose reader treats every scalar as string, like Unity assets
+    pub fn set_plain_scalar(&mut self, range: Range<usize>, value: &str) {
+        let quoted = match self.src[range.clone()].chars().next() {
+            Some('"' | '\'') => return self.set_scalar(range, value),
+            _ if is_plain_safe(value) => value.to_owned(),
+            _ => crate::utils::json::quote_string(value),
+        };
+        self.replacements.retain(|(r, _)| r != &range);
+        self.replacements.push((range, quoted));
+    }
+
+    /// Replaces the scalar with plain integer
+    pub fn set_integer(&mut self, range: Range<usize>, value: u32) {
+        self.replacements.retain(|(r, _)| r != &range);
+        self.replacements.push((range, value.to_string()));
+    }
+}
+
+impl Display for YamlDocument<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut replacements = self.replacements.iter().collect::<Vec<_>>();
+        replacements.sort_by_key(|(range, _)| range.start);
+        let mut pos = 0;
+        for (range, value) in replacements {
+            f.write_str(&self.src[pos..range.start])?;
+            f.write_str(value)?;
+            pos = range.end;
+        }
+        f.write_str(&self.src[pos..])
+    }
+}
+
+/// A non-empty line. for sequence items, the line after `- ` is treated as a separated line
+#[derive(Clone, Copy)]
+struct Line {
+    number: usize,
+    indent: usize,
+    /// start of the content in the source
+    start: usize,
+    /// end of the content in the source, excluding comment and trailing whitespace
+    end: usize,
+}
+
+fn parse_document(src: &str, lines: Vec<Line>) -> Result<YamlNode, YamlError> {
+    let mut parser = Parser { src, lines, pos: 0 };
+    let node = parser.parse_block(None)?;
+    match parser.peek() {
+        Some(line) => Err(YamlError::Unsupported(line.number)),
+        None => Ok(node),
+    }
+}
+
+struct Parser<'src> {
+    src: &'src str,
+    lines: Vec<Line>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<Line> {
+        self.lines.get(self.pos).copied()
+    }
+
+    fn text(&self, line: Line) -> &str {
+        &self.src[line.start..line.end]
+    }
+
+    fn is_sequence_item(&self, line: Line) -> bool {
+        let text = self.text(line);
+        text == "-" || text.starts_with("- ")
+    }
+
+    /// parses block node more indented than parent
+    fn parse_block(&mut self, parent: Option<usize>) -> Result<YamlNode, YamlError> {
+        let Some(line) = self.peek() else {
+            return Ok(YamlNode::Other);
+        };
+        if parent.is_some_and(|parent| line.indent <= parent) {
+            return Ok(YamlNode::Other);
+        }
+
+        if self.is_sequence_item(line) {
+            self.parse_sequence(line.indent)
+        } else if split_key(self.text(line)).is_some() {
+            self.parse_mapping(line.indent)
+        } else {
+            self.pos += 1;
+            let node = self
+                .value_node(line.start, line.end)
+                .unwrap_or(YamlNode::Other);
+            Ok(self.skip_children(parent.unwrap_or(0), node))
+        }
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<YamlNode, YamlError> {
+        let mut entries = Vec::new();
+        while let Some(line) = self.peek() {
+            if line.indent < indent || self.is_sequence_item(line) && line.indent == indent {
+                break;
+            }
+            if line.indent > indent {
+                return Err(YamlError::Unsupported(line.number));
+            }
+            let Some((key, value_start)) = split_key(self.text(line)) else {
+                return Err(YamlError::Unsupported(line.number));
+            };
+            self.pos += 1;
+
+            let value = match self.value_node(line.start + value_start, line.end) {
+                Some(node) => self.skip_children(indent, node),
+                None => match self.peek() {
+                    // sequence can have the same indent as the key
+                    Some(next) if next.indent == indent && self.is_sequence_item(next) => {
+                        self.parse_sequence(indent)?
+                    }
+                    _ => self.parse_block(Some(indent))?,
+                },
+            };
+            entries.push((key, value));
+        }
+        Ok(YamlNode::Mapping(entries))
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<YamlNode, YamlError> {
+        let mut items = Vec::new();
+        while let Some(line) = self.peek() {
+            if line.indent < indent || !self.is_sequence_item(line) && line.indent == indent {
+                break;
+            }
+            if line.indent > indent {
+                return Err(YamlError::Unsupported(line.number));
+            }
+            let rest = &self.src[line.start + 1..line.end];
+            let offset = 1 + rest.len() - rest.trim_start().len();
+            if offset == line.end - line.start {
+                self.pos += 1;
+            } else {
+                // treat the content after `- ` as a line
+                let line = &mut self.lines[self.pos];
+                line.indent += offset;
+                line.start += offset;
+            }
+            items.push(self.parse_block(Some(indent))?);
+        }
+        Ok(YamlNode::Sequence(items))
+    }
+
+    /// Skips lines more indented than `indent`.
+    /// If some lines are skipped, the scalar was multi line one so it's not a scalar.
+    fn skip_children(&mut self, indent: usize, node: YamlNode) -> YamlNode {
+        let mut skipped = false;
+        while self.peek().is_some_and(|line| line.indent > indent) {
+            self.pos += 1;
+            skipped = true;
+        }
+        if skipped {
+            YamlNode::Other
+        } else {
+            node
+        }
+    }
+
+    /// Creates node for the inline value. anchors and tags are skipped.
+    /// Returns None if there's no inline value.
+    fn value_node(&self, mut start: usize, end: usize) -> Option<YamlNode> {
+        while self.src[start..end].starts_with(['&', '!']) {
+            start += self.src[start..end].find(' ')?;
+            start += self.src[start..end].len() - self.src[start..end].trim_start().len();
+        }
+        match self.src[start..end].chars().next()? {
+            '[' | '{' | '*' | '|' | '>' => Some(YamlNode::Other),
+            _ => Some(YamlNode::Scalar(start..end)),
+        }
+    }
+}
+
+/// Returns the end of the line content excluding comment and trailing whitespace
+fn content_end(line: &str) -> usize {
+    let mut quote = None;
+    let mut prev = ' ';
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (None, '#') if prev.is_whitespace() => return line[..i].trim_end().len(),
+            (None, '"' | '\'') if prev.is_whitespace() || matches!(prev, '[' | '{' | ',') => {
+                quote = Some(c)
+            }
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some('\''), '\'') if chars.peek().is_some_and(|&(_, c)| c == '\'') => {
+                chars.next();
+            }
+            (Some(q), c) if q == c => quote = None,
+            _ => {}
+        }
+        prev = c;
+    }
+    line.trim_end().len()
+}
+
+/// Splits `key: value` and returns the unquoted key and the offset of the value
+fn split_key(text: &str) -> Option<(String, usize)> {
+    let key_end = if text.starts_with(['"', '\'']) {
+        let quote = text.chars().next().unwrap();
+        let mut end = None;
+        let mut chars = text.char_indices().skip(1).peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' if quote == '"' => {
+                    chars.next();
+                }
+                '\'' if quote == '\'' && chars.peek().is_some_and(|&(_, c)| c == '\'') => {
+                    chars.next();
+                }
+                c if c == quote => {
+                    end = Some(i + 1);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let end = end?;
+        let colon = end + (text[end..].len() - text[end..].trim_start().len());
+        text[colon..].starts_with(':').then_some(colon)?
+    } else {
+        if text.starts_with(['-', '?', '[', '{', '#']) && !text.starts_with("-:") {
+            return None;
+        }
+        text.match_indices(':')
+            .map(|(i, _)| i)
+            .find(|&i| text[i + 1..].chars().next().is_none_or(|c| c == ' '))?
+    };
+
+    let key = unquote(text[..key_end].trim_end());
+    let value = &text[key_end + 1..];
+    let value_start = key_end + 1 + (value.len() - value.trim_start().len());
+    Some((key, value_start))
+}
+
+fn unquote(text: &str) -> String {
+    if let Some(inner) = text.strip_prefix('"').and_then(|x| x.strip_suffix('"')) {
+        let mut result = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            let hex = |chars: &mut std::str::Chars, len: usize| {
+                let hex = chars.as_str().get(..len)?;
+                let c = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+                chars.nth(len - 1);
+                Some(c)
+            };
+            match chars.next() {
+                Some('0') => result.push('\0'),
+                Some('t') => result.push('\t'),
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some('x') => result.extend(hex(&mut chars, 2)),
+                Some('u') => result.extend(hex(&mut chars, 4)),
+                Some('U') => result.extend(hex(&mut chars, 8)),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            }
+        }
+        result
+    } else if let Some(inner) = text.strip_prefix('\'').and_then(|x| x.strip_suffix('\'')) {
+        inner.replace("''", "'")
+    } else {
+        text.to_owned()
+    }
+}
+
+fn is_plain_safe(value: &str) -> bool {
+    !value.is_empty()
+        && value.trim() == value
+        && !value.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        && !value.ends_with(':')
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value.contains(|c: char| c.is_control())
+}
+
+/// Returns true if the plain scalar is resolved to int, float, bool or null in core schema.
+/// YAML 1.1 booleans like `yes` and `off` are included since some readers still use them
+fn resolves_to_non_string(value: &str) -> bool {
+    static PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"(?x)^(?:
+            ~|null|Null|NULL
+            |true|True|TRUE|false|False|FALSE
+            |y|Y|yes|Yes|YES|n|N|no|No|NO|on|On|ON|off|Off|OFF
+            |[-+]?[0-9]+|0o[0-7]+|0x[0-9a-fA-F]+
+            |[-+]?(?:\.[0-9]+|[0-9]+(?:\.[0-9]*)?)(?:[eE][-+]?[0-9]+)?
+            |[-+]?\.(?:inf|Inf|INF)|\.nan|\.NaN|\.NAN
+        )$",
+        )
+        .unwrap()
+    });
+    PATTERN.is_match(value)
+}
+
+#[test]
+fn parse_and_modify() {
+    let src = r#"# comment
+apiVersion: v2
+name: "example" # name
+version: 1.0.0
+appVersion: '1.0.0'
+description: |
+  multi line
+  version: 2.0.0
+dependencies:
+  - name: common
+    version: &common 1.0.0 # local
+    repository: file://../common
+  - name: redis
+    version: "17.0.0"
+    repository: https://charts.bitnami.com/bitnami
+list:
+- a
+- - nested
+- b
+flow: [1, 2]
+alias: *common
+"key: with colon": value
+"#;
+    let mut document = YamlDocument::parse(src).unwrap();
+    let root = document.root();
+    let scalar = |path: &[&str]| document.scalar(root.find(path).unwrap().as_scalar().unwrap());
+    assert_eq!(scalar(&["name"]), "example");
+    assert_eq!(scalar(&["version"]), "1.0.0");
+    assert_eq!(scalar(&["appVersion"]), "1.0.0");
+    assert_eq!(scalar(&["dependencies", "0", "version"]), "1.0.0");
+    assert_eq!(scalar(&["dependencies", "1", "version"]), "17.0.0");
+    assert_eq!(
+        scalar(&["dependencies", "1", "repository"]),
+        "https://charts.bitnami.com/bitnami"
+    );
+    assert_eq!(scalar(&["list", "1", "0"]), "nested");
+    assert_eq!(scalar(&["list", "2"]), "b");
+    assert_eq!(scalar(&["key: with colon"]), "value");
+    assert!(matches!(root.get("description"), Some(YamlNode::Other)));
+    assert!(matches!(root.get("flow"), Some(YamlNode::Other)));
+    assert!(matches!(root.get("alias"), Some(YamlNode::Other)));
+
+    let version = root.get("version").unwrap().as_scalar().unwrap();
+    let app_version = root.get("appVersion").unwrap().as_scalar().unwrap();
+    let dependency = root
+        .find(&["dependencies", "0", "version"])
+        .unwrap()
+        .as_scalar()
+        .unwrap();
+    let name = root.get("name").unwrap().as_scalar().unwrap();
+    document.set_scalar(version, "2.0.0-beta.1");
+    document.set_scalar(app_version, "it's");
+    document.set_scalar(dependency, "2.0.0");
+    document.set_scalar(name, "a\"b");
+
+    assert_eq!(
+        document.to_string(),
+        src.replacen("version: 1.0.0", "version: 2.0.0-beta.1", 1)
+            .replace("'1.0.0'", "'it''s'")
+            .replace("&common 1.0.0", "&common 2.0.0")
+            .replace(r#""example""#, r#""a\"b""#)
+    );
+}
+
+#[test]
+fn quote_non_string() {
+    let src = "version: 1.0.0\n";
+    let mut document = YamlDocument::parse(src).unwrap();
+    let version = document.root().get("version").unwrap().as_scalar().unwrap();
+    document.set_scalar(version, "1.10");
+    assert_eq!(document.to_string(), "version: \"1.10\"\n");
+
+    let document = YamlDocument::parse("version: \"1.10\"\n").unwrap();
+    let version = document.root().get("version").unwrap().as_scalar().unwrap();
+    assert_eq!(document.scalar(version), "1.10");
+
+    // the type is not changed
+    let mut document = YamlDocument::parse("version: 1.9\n").unwrap();
+    let version = document.root().get("version").unwrap().as_scalar().unwrap();
+    document.set_scalar(version.clone(), "1.10");
+    assert_eq!(document.to_string(), "version: 1.10\n");
+    document.set_scalar(version, "1.10.0");
+    assert_eq!(document.to_string(), "version: \"1.10.0\"\n");
+
+    let mut document = YamlDocument::parse(src).unwrap();
+    let version = document.root().get("version").unwrap().as_scalar().unwrap();
+    document.set_plain_scalar(version, "1.10");
+    assert_eq!(document.to_string(), "version: 1.10\n");
+
+    for value in ["2.0", "1e3", "0x1F", "true", "null", "~", ".5", "-1", "off"] {
+        assert!(resolves_to_non_string(value), "{value}");
+    }
+    for value in ["1.0.0", "2.0.0-beta.1", "v1", "1.0.0+build.1"] {
+        assert!(!resolves_to_non_string(value), "{value}");
+    }
+}
+
+#[test]
+fn multiple_documents() {
+    let src = "%YAML 1.1\n---\nkind: Service\n---\nkind: Deployment\nspec:\n  version: 0.1\n";
+    let document = YamlDocument::parse(src).unwrap();
+    assert_eq!(document.documents().len(), 2);
+    let version = document.documents()[1]
+        .find(&["spec", "version"])
+        .unwrap()
+        .as_scalar()
+        .unwrap();
+    assert_eq!(document.scalar(version), "0.1");
+}
@@ -0,0 +1,362 @@
+use crate::utils::gradle::escape_groovy_string;
+use crate::utils::properties::PropertiesFile;
+use crate::version_changer::gradle_build_script::{
+    assignment_value, block_statements, parse_string_assignment, VersionLiteral,
+};
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Sets `versionName` and `versionCode` in `android.defaultConfig` of the build script, or in gradle.properties
+#[derive(Debug, Deserialize)]
+pub(crate) struct Android {
+    /// path to build script or `.properties` file.
+    /// if not specified, app/build.gradle.kts or app/build.gradle is used
+    path: Option<PathBuf>,
+    /// `increment` or formula with `major`, `minor`, `patch`, integers, `+` and `*`
+    /// like `major*10000 + minor*100 + patch`
+    #[serde(default = "version_code_default", alias = "info")]
+    version_code: String,
+}
+
+fn version_code_default() -> String {
+    "increment".to_owned()
+}
+
+impl Display for Android {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(
+                f,
+                "android(at {} code {})",
+                path.display(),
+                self.version_code
+            ),
+            None => write!(f, "android(code {})", self.version_code),
+        }
+    }
+}
+
+impl Android {
+    async fn path(&self) -> PathBuf {
+        if let Some(path) = &self.path {
+            return path.clone();
+        }
+        let kts = PathBuf::from("app/build.gradle.kts");
+        if tokio::fs::try_exists(&kts)
+            .await
+            .expect("checking app/build.gradle.kts")
+        {
+            kts
+        } else {
+            PathBuf::from("app/build.gradle")
+        }
+    }
+
+    fn next_code(&self, version: &str, current_name: &str, current: u32) -> u32 {
+        let next = if self.version_code == "increment" {
+            current + 1
+        } else {
+            eval_formula(&self.version_code, version)
+        };
+        // the same versionCode cannot be used for another versionName
+        if next < current || next == current && version != current_name {
+            panic!(
+                "versionCode {next} for {version} is not greater than current versionCode {current}"
+            );
+        }
+        next
+    }
+}
+
+fn is_properties(path: &Path) -> bool {
+    path.extension().is_some_and(|x| x == "properties")
+}
+
+impl VersionChanger for Android {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let version_code = info.map(Into::into).unwrap_or_else(version_code_default);
+        if version_code != "increment" {
+            // check the formula
+            eval_formula(&version_code, "0.0.0");
+        }
+        Self {
+            path: path.map(Into::into),
+            version_code,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let path = self.path().await;
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+        if is_properties(&path) {
+            let properties = content
+                .parse::<PropertiesFile>()
+                .unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e));
+            properties
+                .get("versionName")
+                .unwrap_or_else(|| panic!("no versionName in {}", path.display()))
+        } else {
+            find_version_name(&content).value
+        }
+    }
+
+    async fn set_version(&self, version: &str) {
+        let path = self.path().await;
+        let mut content = tokio::fs::read_to_string(&path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+        if is_properties(&path) {
+            let mut properties = content
+                .parse::<PropertiesFile>()
+                .unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e));
+            let current = properties
+                .get("versionCode")
+                .unwrap_or_else(|| panic!("no versionCode in {}", path.display()));
+            let current = current
+                .parse()
+                .unwrap_or_else(|_| panic!("versionCode is not an integer: {current}"));
+            let current_name = properties
+                .get("versionName")
+                .unwrap_or_else(|| panic!("no versionName in {}", path.display()));
+            let code = self.next_code(version, &current_name, current);
+            properties.set("versionName", version.to_owned());
+            properties.set("versionCode", code.to_string());
+            content = properties.to_string();
+        } else {
+            let name = find_version_name(&content);
+            let code = find_version_code(&content);
+            let current = content[code.clone()].parse().unwrap();
+            let next = self.next_code(version, &name.value, current);
+            let name = name.literal;
+
+            let quote = &content[name.start..name.start + 1];
+            let name_replacement = format!("{quote}{}{quote}", escape_groovy_string(version));
+            // replace later one first to keep the range of the other
+            let mut replacements = [(name, name_replacement), (code, next.to_string())];
+            replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+            for (range, replacement) in replacements {
+                content.replace_range(range, &replacement);
+            }
+        }
+        tokio::fs::write(&path, content)
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+    }
+}
+
+/// Returns the statements in `android.defaultConfig` block assigning the property
+fn default_config_statements(script: &str, name: &str) -> Vec<Range<usize>> {
+    let statements = block_statements(script)
+        .into_iter()
+        .filter(|(blocks, _)| blocks == &["android", "defaultConfig"])
+        .map(|(_, statement)| statement)
+        .filter(|statement| assignment_value(script, statement.clone(), name).is_some())
+        .collect::<Vec<_>>();
+    if statements.len() != 1 {
+        panic!(
+            "expected one {name} in android.defaultConfig but found {}",
+            statements.len()
+        );
+    }
+    statements
+}
+
+fn find_version_name(script: &str) -> VersionLiteral {
+    let statement = default_config_statements(script, "versionName").remove(0);
+    parse_string_assignment(script, statement.clone(), "versionName").unwrap_or_else(|| {
+        panic!(
+            "versionName is not a string literal in build script: {}",
+            script[statement].trim()
+        )
+    })
+}
+
+/// Returns the range of integer literal of versionCode
+fn find_version_code(script: &str) -> Range<usize> {
+    let statement = default_config_statements(script, "versionCode").remove(0);
+    let value = assignment_value(script, statement.clone(), "versionCode").unwrap();
+    if script[value.clone()].parse::<u32>().is_err() {
+        panic!(
+            "versionCode is not an integer literal in build script: {}",
+            script[statement].trim()
+        );
+    }
+    value
+}
+
+/// Evaluates formula like `major*10000 + minor*100 + patch`. prerelease and build metadata are ignored.
+/// Panics if a lower part is too large for the formula and would collide with the code of another version,
+/// like `1.100.0` and `2.0.0` for the formula above
+pub(super) fn eval_formula(formula: &str, version: &str) -> u32 {
+    const NAMES: [&str; 3] = ["major", "minor", "patch"];
+    // coefficients of major, minor, patch and the constant term
+    let mut coefficients = [0u32; 4];
+    for term in formula.split('+') {
+        let mut variable = None;
+        let mut coefficient = 1u32;
+        for factor in term.split('*').map(str::trim) {
+            if let Some(index) = NAMES.iter().position(|x| *x == factor) {
+                if variable.replace(index).is_some() {
+                    panic!("invalid version code formula {formula}: multiple parts in {term}");
+                }
+            } else {
+                let number = factor
+                    .parse::<u32>()
+                    .unwrap_or_else(|_| panic!("invalid version code formula {formula}: {factor}"));
+                coefficient = coefficient
+                    .checked_mul(number)
+                    .unwrap_or_else(|| panic!("invalid version code formula {formula}: overflow"));
+            }
+        }
+        let index = variable.unwrap_or(3);
+        coefficients[index] = coefficients[index]
+            .checked_add(coefficient)
+            .unwrap_or_else(|| panic!("invalid version code formula {formula}: overflow"));
+    }
+
+    let release = version.split(['-', '+']).next().unwrap();
+    let parts = release
+        .split('.')
+        .map(|x| {
+            x.parse::<u32>()
+                .unwrap_or_else(|_| panic!("cannot compute version code from {version}"))
+        })
+        .collect::<Vec<_>>();
+    if parts.len() > 3 {
+        panic!("cannot compute version code from {version}: more than 3 parts");
+    }
+    let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+    let overflow = || panic!("cannot compute version code from {version} with {formula}: overflow");
+
+    // the sum of terms of lower parts must be less than the coefficient of each part
+    let mut lower = 0u32;
+    for i in (0..3).rev() {
+        if coefficients[i] != 0 && lower >= coefficients[i] {
+            panic!(
+                "cannot compute version code from {version} with {formula}: {} is too large",
+                NAMES[i + 1..].join(" or ")
+            );
+        }
+        lower = part(i)
+            .checked_mul(coefficients[i])
+            .and_then(|x| x.checked_add(lower))
+            .unwrap_or_else(overflow);
+    }
+    lower.checked_add(coefficients[3]).unwrap_or_else(overflow)
+}
+
+#[test]
+fn eval_formula_test() {
+    let formula = "major*10000 + minor*100 + patch";
+    assert_eq!(eval_formula(formula, "1.2.3"), 10203);
+    assert_eq!(eval_formula(formula, "2.0-SNAPSHOT"), 20000);
+    assert_eq!(eval_formula("major * 1000000 + 5", "3.0.0"), 3000005);
+    assert_eq!(eval_formula(formula, "1.99.99"), 19999);
+}
+
+#[test]
+#[should_panic(expected = "minor or patch is too large")]
+fn eval_formula_collision() {
+    eval_formula("major*10000 + minor*100 + patch", "1.100.0");
+}
+
+#[tokio::test]
+async fn build_script() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("build.gradle.kts");
+    let source = r#"plugins {
+    id("com.android.application")
+}
+
+android {
+    namespace = "com.example.app"
+
+    defaultConfig {
+        applicationId = "com.example.app"
+        minSdk = 24
+        versionCode = 10203
+        versionName = "1.2.3"
+    }
+
+    productFlavors {
+        create("demo") {
+            versionNameSuffix = "-demo"
+            versionCode = 1
+        }
+    }
+}
+"#;
+    std::fs::write(&path, source).unwrap();
+    let path = path.to_str();
+
+    let changer = Android::parse(Some("major*10000 + minor*100 + patch"), path);
+    assert_eq!(changer.load_version().await, "1.2.3");
+    changer.set_version("1.3.0").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        source
+            .replace("versionCode = 10203", "versionCode = 10300")
+            .replace("versionName = \"1.2.3\"", "versionName = \"1.3.0\"")
+    );
+
+    Android::parse(None, path).set_version("1.3.1").await;
+    let content = std::fs::read_to_string(path.unwrap()).unwrap();
+    assert!(content.contains("versionCode = 10301\n        versionName = \"1.3.1\""));
+}
+
+#[tokio::test]
+async fn groovy_and_properties() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("build.gradle");
+    std::fs::write(
+        &script,
+        "android {\n    defaultConfig {\n        versionCode 7\n        versionName '1.0'\n    }\n}\n",
+    )
+    .unwrap();
+    Android::parse(None, script.to_str())
+        .set_version("1.1")
+        .await;
+    assert_eq!(
+        std::fs::read_to_string(&script).unwrap(),
+        "android {\n    defaultConfig {\n        versionCode 8\n        versionName '1.1'\n    }\n}\n"
+    );
+
+    let properties = dir.path().join("gradle.properties");
+    std::fs::write(&properties, "versionName=1.0.0\nversionCode=5\n").unwrap();
+    let changer = Android::parse(None, properties.to_str());
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.0.1").await;
+    assert_eq!(
+        std::fs::read_to_string(&properties).unwrap(),
+        "versionName=1.0.1\nversionCode=6\n"
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "is not greater than current versionCode 20000")]
+async fn lower_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let properties = dir.path().join("gradle.properties");
+    std::fs::write(&properties, "versionName=2.0.0\nversionCode=20000\n").unwrap();
+    Android::parse(Some("major*10000 + minor*100 + patch"), properties.to_str())
+        .set_version("1.9.0")
+        .await;
+}
+
+#[tokio::test]
+#[should_panic(
+    expected = "versionCode 10300 for 1.3.0 is not greater than current versionCode 10300"
+)]
+async fn same_code_for_another_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let properties = dir.path().join("gradle.properties");
+    std::fs::write(&properties, "versionName=1.3.0-rc.1\nversionCode=10300\n").unwrap();
+    Android::parse(Some("major*10000 + minor*100 + patch"), properties.to_str())
+        .set_version("1.3.0")
+        .await;
+}
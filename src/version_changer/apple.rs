@@ -0,0 +1,411 @@
+use crate::utils;
+use crate::utils::xml::{ElementId, XmlDocument};
+use crate::version_changer::VersionChanger;
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Sets `CFBundleShortVersionString` in `Info.plist` or `MARKETING_VERSION` in `project.pbxproj`,
+/// and optionally `CFBundleVersion` or `CURRENT_PROJECT_VERSION` as the build number.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Apple {
+    /// path to `Info.plist` or `project.pbxproj`. if not specified, `*.xcodeproj/project.pbxproj` is used
+    path: Option<PathBuf>,
+    #[serde(default, alias = "info")]
+    build_number: BuildNumber,
+    #[serde(default)]
+    prerelease: PrereleaseMode,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum BuildNumber {
+    #[default]
+    #[serde(rename = "keep")]
+    Keep,
+    /// adds one to the current build number
+    #[serde(rename = "increment")]
+    Increment,
+    /// uses the version as the build number
+    #[serde(rename = "version")]
+    Version,
+}
+
+/// Apple only allows up to three dot-separated integers for the version and the build number
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum PrereleaseMode {
+    /// panics for versions with prerelease or build metadata
+    #[default]
+    #[serde(rename = "error")]
+    Error,
+    /// removes prerelease and build metadata: `1.2.0-rc.1` is written as `1.2.0`
+    #[serde(rename = "strip")]
+    Strip,
+}
+
+impl Display for Apple {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "apple(at {})", path.display()),
+            None => write!(f, "apple"),
+        }
+    }
+}
+
+/// The file format with keys for the version and the build number
+#[derive(Clone, Copy)]
+enum Format {
+    Plist,
+    Pbxproj,
+}
+
+impl Format {
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("plist") => Format::Plist,
+            Some("pbxproj") => Format::Pbxproj,
+            _ => panic!(
+                "apple version changer supports .plist or .pbxproj: {}",
+                path.display()
+            ),
+        }
+    }
+
+    fn version_key(self) -> &'static str {
+        match self {
+            Format::Plist => "CFBundleShortVersionString",
+            Format::Pbxproj => "MARKETING_VERSION",
+        }
+    }
+
+    fn build_key(self) -> &'static str {
+        match self {
+            Format::Plist => "CFBundleVersion",
+            Format::Pbxproj => "CURRENT_PROJECT_VERSION",
+        }
+    }
+
+    /// Returns the unquoted values of all occurrences of the key
+    fn values(self, source: &str, key: &str) -> Vec<String> {
+        match self {
+            Format::Plist => {
+                let document = parse_plist(source);
+                let element = plist_value(&document, key);
+                vec![document.text(element).unwrap_or_default()]
+            }
+            Format::Pbxproj => find_build_settings(source, key)
+                .into_iter()
+                .map(|(_, value)| value)
+                .collect(),
+        }
+    }
+}
+
+impl Apple {
+    fn path(&self) -> PathBuf {
+        if let Some(path) = &self.path {
+            return path.clone();
+        }
+        let mut found = utils::glob::expand_path("*.xcodeproj/project.pbxproj");
+        if found.len() != 1 {
+            panic!(
+                "apple version changer needs path: {} xcode projects found",
+                found.len()
+            );
+        }
+        found.remove(0)
+    }
+}
+
+impl VersionChanger for Apple {
+    /// `apple:[build number mode][,prerelease mode]@path` like `apple:increment,strip`
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let mut build_number = BuildNumber::Keep;
+        let mut prerelease = PrereleaseMode::Error;
+        for option in info.into_iter().flat_map(|x| x.split(',')) {
+            match option {
+                "keep" => build_number = BuildNumber::Keep,
+                "increment" => build_number = BuildNumber::Increment,
+                "version" => build_number = BuildNumber::Version,
+                "error" => prerelease = PrereleaseMode::Error,
+                "strip" => prerelease = PrereleaseMode::Strip,
+                option => panic!("invalid apple version changer: unknown option {option}"),
+            }
+        }
+        Self {
+            path: path.map(Into::into),
+            build_number,
+            prerelease,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let path = self.path();
+        let format = Format::of(&path);
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+        agreed_value(
+            &path,
+            format.version_key(),
+            format.values(&source, format.version_key()),
+        )
+    }
+
+    async fn set_version(&self, version: &str) {
+        let version = match self.prerelease {
+            PrereleaseMode::Error => version,
+            PrereleaseMode::Strip => version.split(['-', '+']).next().unwrap(),
+        };
+        let parts = version.split('.').collect::<Vec<_>>();
+        let numeric = |x: &&str| !x.is_empty() && x.bytes().all(|x| x.is_ascii_digit());
+        if parts.len() > 3 || !parts.iter().all(numeric) {
+            panic!("apple version must be up to three integers like 1.2.3: {version}");
+        }
+
+        let path = self.path();
+        let format = Format::of(&path);
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+        // check the current version is editable here
+        agreed_value(
+            &path,
+            format.version_key(),
+            format.values(&source, format.version_key()),
+        );
+
+        let build_number = match self.build_number {
+            BuildNumber::Keep => None,
+            BuildNumber::Version => Some(version.to_owned()),
+            BuildNumber::Increment => {
+                let key = format.build_key();
+                let current = agreed_value(&path, key, format.values(&source, key));
+                let current = current.parse::<u64>().unwrap_or_else(|_| {
+                    panic!("{key} in {} is not an integer: {current}", path.display())
+                });
+                Some((current + 1).to_string())
+            }
+        };
+
+        let updated = match format {
+            Format::Plist => {
+                let mut document = parse_plist(&source);
+                let element = plist_value(&document, format.version_key());
+                document.set_text(element, version);
+                if let Some(build_number) = &build_number {
+                    let element = plist_value(&document, format.build_key());
+                    document.set_text(element, build_number);
+                }
+                document.to_string()
+            }
+            Format::Pbxproj => {
+                // the values are numeric so quotes are kept as is and not needed for new values
+                let replace = |key: &str, value: &str| {
+                    find_build_settings(&source, key)
+                        .into_iter()
+                        .map(|(range, _)| {
+                            if source[range.clone()].starts_with('"') {
+                                (range, format!("\"{value}\""))
+                            } else {
+                                (range, value.to_owned())
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let mut replacements = replace(format.version_key(), version);
+                if let Some(build_number) = &build_number {
+                    replacements.extend(replace(format.build_key(), build_number));
+                }
+                replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+                let mut updated = source.clone();
+                for (range, value) in replacements {
+                    updated.replace_range(range, &value);
+                }
+                updated
+            }
+        };
+
+        tokio::fs::write(&path, updated)
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+    }
+}
+
+/// Returns the value all occurrences agree on
+fn agreed_value(path: &Path, key: &str, values: Vec<String>) -> String {
+    let mut values = values.into_iter();
+    let first = values
+        .next()
+        .unwrap_or_else(|| panic!("no {key} in {}", path.display()));
+    if let Some(other) = values.find(|x| x != &first) {
+        panic!("{key} mismatch in {}: {first} != {other}", path.display());
+    }
+    if first.starts_with("$(") || first.starts_with("${") {
+        panic!(
+            "{key} in {} refers build setting {first}; change project.pbxproj instead",
+            path.display()
+        );
+    }
+    first
+}
+
+fn parse_plist(source: &str) -> XmlDocument<'_> {
+    if source.starts_with("bplist") {
+        panic!("binary property list is not supported");
+    }
+    XmlDocument::parse(source).unwrap_or_else(|e| panic!("parsing property list: {e}"))
+}
+
+/// Returns the value element for the key in the top-level dict
+fn plist_value(document: &XmlDocument, key: &str) -> ElementId {
+    let dict = document.find(&["dict"]).expect("no dict in property list");
+    let mut children = document.children(dict);
+    while let Some(child) = children.next() {
+        if document.name(child) == "key" && document.text(child).as_deref() == Some(key) {
+            return children
+                .next()
+                .filter(|&x| document.name(x) == "string")
+                .unwrap_or_else(|| panic!("{key} is not a string in property list"));
+        }
+    }
+    panic!("no {key} in property list")
+}
+
+/// Finds `KEY = value;` in the build settings and returns the ranges of the values
+fn find_build_settings(source: &str, key: &str) -> Vec<(Range<usize>, String)> {
+    let regex = Regex::new(&format!(
+        r#"(?:^|[\s{{;]){}\s*=\s*("(?:[^"\\]|\\.)*"|[^";\s]+)\s*;"#,
+        regex::escape(key)
+    ))
+    .unwrap();
+    regex
+        .captures_iter(source)
+        .map(|captures| {
+            let value = captures.get(1).unwrap();
+            let text = value.as_str();
+            let unquoted = match text.strip_prefix('"') {
+                Some(quoted) => unescape_pbxproj(&quoted[..quoted.len() - 1]),
+                None => text.to_owned(),
+            };
+            (value.range(), unquoted)
+        })
+        .collect()
+}
+
+fn unescape_pbxproj(quoted: &str) -> String {
+    let mut result = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(c) => result.push(c),
+                None => {}
+            },
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[tokio::test]
+async fn info_plist() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Info.plist");
+    let source = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>CFBundleName</key>
+	<string>Example</string>
+	<key>CFBundleShortVersionString</key>
+	<string>1.0.0</string>
+	<key>CFBundleVersion</key>
+	<string>12</string>
+</dict>
+</plist>
+"#;
+    std::fs::write(&path, source).unwrap();
+    let path = path.to_str();
+
+    let changer = Apple::parse(Some("increment"), path);
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        source
+            .replace("<string>1.0.0</string>", "<string>1.1.0</string>")
+            .replace("<string>12</string>", "<string>13</string>")
+    );
+}
+
+#[tokio::test]
+async fn pbxproj() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.pbxproj");
+    let source = r#"// !$*UTF8*$!
+{
+	objects = {
+		1A2B3C4D /* Debug */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				CURRENT_PROJECT_VERSION = 7;
+				INFOPLIST_KEY_CFBundleDisplayName = Example;
+				MARKETING_VERSION = 1.0.0;
+			};
+			name = Debug;
+		};
+		1A2B3C4E /* Release */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				CURRENT_PROJECT_VERSION = 7;
+				MARKETING_VERSION = "1.0.0";
+			};
+			name = Release;
+		};
+	};
+}
+"#;
+    std::fs::write(&path, source).unwrap();
+    let path = path.to_str();
+
+    let changer = Apple::parse(Some("increment,strip"), path);
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0-beta+1").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        source
+            .replace("MARKETING_VERSION = 1.0.0;", "MARKETING_VERSION = 1.1.0;")
+            .replace(
+                "MARKETING_VERSION = \"1.0.0\";",
+                "MARKETING_VERSION = \"1.1.0\";"
+            )
+            .replace(
+                "CURRENT_PROJECT_VERSION = 7;",
+                "CURRENT_PROJECT_VERSION = 8;"
+            )
+    );
+    assert_eq!(changer.load_version().await, "1.1.0");
+
+    let changer = Apple::parse(Some("version,strip"), path);
+    changer.set_version("1.2.0-rc.1").await;
+    let content = std::fs::read_to_string(path.unwrap()).unwrap();
+    assert_eq!(
+        content.matches("CURRENT_PROJECT_VERSION = 1.2.0;").count(),
+        2
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "apple version must be up to three integers like 1.2.3: 1.1.0-beta.1")]
+async fn prerelease_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.pbxproj");
+    std::fs::write(&path, "{ MARKETING_VERSION = 1.0.0; }\n").unwrap();
+    Apple::parse(Some("version"), path.to_str())
+        .set_version("1.1.0-beta.1")
+        .await;
+}
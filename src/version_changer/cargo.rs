@@ -5,36 +5,122 @@
 
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use cargo_edit::{LocalManifest, upgrade_requirement};
 use cargo_metadata::semver;
 use serde::Deserialize;
-use log::{debug};
+use log::{debug, info};
+use crate::utils;
 use crate::version_changer::VersionChanger;
 
 #[derive(Debug, Deserialize)]
 pub struct Cargo {
     manifest_path: Option<PathBuf>,
     package: Option<String>,
+    /// names or glob patterns of packages versioned independently.
+    /// dependents whose requirements break are bumped with `cascade`
+    #[serde(default)]
+    packages: Vec<String>,
+    #[serde(default)]
+    cascade: CascadeBump,
+    #[serde(default)]
+    mode: CargoMode,
+}
+
+/// The bump for dependents to be re-released
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum CascadeBump {
+    #[serde(rename = "major")]
+    Major,
+    #[serde(rename = "minor")]
+    Minor,
+    #[default]
+    #[serde(rename = "patch")]
+    Patch,
+}
+
+impl CascadeBump {
+    /// Bumps the version. prerelease and build metadata are dropped since the dependent is released
+    /// as a stable version: `1.2.0-beta.1` with patch becomes `1.2.1`
+    fn apply(self, version: &str) -> semver::Version {
+        let mut version = semver::Version::parse(version)
+            .unwrap_or_else(|e| panic!("version {version} is not semver: {e}"));
+        match self {
+            CascadeBump::Major => {
+                version.major += 1;
+                version.minor = 0;
+                version.patch = 0;
+            }
+            CascadeBump::Minor => {
+                version.minor += 1;
+                version.patch = 0;
+            }
+            CascadeBump::Patch => version.patch += 1,
+        }
+        version.pre = semver::Prerelease::EMPTY;
+        version.build = semver::BuildMetadata::EMPTY;
+        version
+    }
+}
+
+/// How the workspace is resolved
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum CargoMode {
+    /// uses `cargo metadata` if cargo is available, otherwise reads manifests
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    /// uses `cargo metadata` and let cargo update Cargo.lock
+    #[serde(rename = "metadata")]
+    Metadata,
+    /// reads manifests and updates Cargo.lock without cargo
+    #[serde(rename = "manifest")]
+    Manifest,
+}
+
+/// a package in the workspace
+struct Package {
+    name: String,
+    version: String,
+    manifest_path: PathBuf,
+}
+
+struct Workspace {
+    root_manifest_path: PathBuf,
+    packages: Vec<Package>,
 }
 
 impl Display for Cargo {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "cargo(at {:?}, package {:?})", self.manifest_path, self.package)
+        if self.packages.is_empty() {
+            write!(f, "cargo(at {:?}, package {:?})", self.manifest_path, self.package)
+        } else {
+            write!(f, "cargo(at {:?}, packages {:?})", self.manifest_path, self.packages)
+        }
     }
 }
 
 impl VersionChanger for Cargo {
     fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        // comma separated names or globs selects multiple packages
+        let (package, packages) = match info {
+            Some(info) if info.contains([',', '*', '?']) => {
+                (None, info.split(',').map(|x| x.trim().to_owned()).collect())
+            }
+            _ => (info.map(|s| s.to_owned()), vec![]),
+        };
         Self {
             manifest_path: path.map(Into::into),
-            package: info.map(|s| s.to_owned()),
+            package,
+            packages,
+            cascade: CascadeBump::Patch,
+            mode: CargoMode::Auto,
         }
     }
 
     async fn load_version(&self) -> String {
-        let ws_metadata = cargo_metadata(self.manifest_path.as_deref(), true);
-        let root_manifest_path = ws_metadata.workspace_root.as_std_path().join("Cargo.toml");
-        let ws_manifest = LocalManifest::try_new(&root_manifest_path)
+        let workspace = self.load_workspace();
+        let ws_manifest = LocalManifest::try_new(&workspace.root_manifest_path)
             .expect("loading manifest of workspace root");
 
         if ws_manifest.data.get("workspace").is_none() {
@@ -42,12 +128,19 @@ impl VersionChanger for Cargo {
                 panic!("to upgrade version name of specific package, don't specify package name");
             }
 
-            ws_metadata.packages[0].version.to_string()
+            workspace.packages[0].version.clone()
+        } else if !self.packages.is_empty() {
+            // selected packages should have the same version
+            let selected = self.selected_packages(&workspace);
+            if let Some(other) = selected.iter().find(|p| p.version != selected[0].version) {
+                panic!("version mismatch: {} is {} but {} is {}", selected[0].name, selected[0].version, other.name, other.version);
+            }
+            selected[0].version.clone()
         } else if let Some(name) = self.package.as_deref() {
             // name specified: get version of specified package in workspace
-            let package = ws_metadata.packages.iter().find(|p| name == p.name.as_ref())
+            let package = workspace.packages.iter().find(|p| name == p.name)
                 .expect("no package with specified name found");
-            package.version.to_string()
+            package.version.clone()
         } else {
             // name not specified: get version of workspace
             ws_manifest.get_workspace_version()
@@ -59,23 +152,23 @@ impl VersionChanger for Cargo {
     async fn set_version(&self, version: &str) {
         let version = semver::Version::parse(version).expect("version name is not semver; cargo doesn't support non-semver");
 
-        let ws_metadata = cargo_metadata(self.manifest_path.as_deref(), true);
-        let root_manifest_path = ws_metadata.workspace_root.as_std_path().join("Cargo.toml");
-        let ws_manifest = LocalManifest::try_new(&root_manifest_path)
+        let workspace = self.load_workspace();
+        let root_manifest_path = &workspace.root_manifest_path;
+        let ws_manifest = LocalManifest::try_new(root_manifest_path)
             .expect("loading manifest of workspace root");
 
-        if ws_manifest.data.get("workspace").is_none() {
+        let updated: Vec<(&Package, semver::Version)> = if ws_manifest.data.get("workspace").is_none() {
             // it's not workspace (single package) project
             if self.package.is_some() {
                 panic!("to upgrade version name of specific package, don't specify package name");
             }
-            if ws_metadata.packages.len() != 1 {
+            if workspace.packages.len() != 1 {
                 panic!("Logic failure: non workspace project has more than one package");
             }
 
-            let the_package = &ws_metadata.packages[0];
+            let the_package = &workspace.packages[0];
 
-            let mut manifest = LocalManifest::try_new(Path::new(&the_package.manifest_path))
+            let mut manifest = LocalManifest::try_new(&the_package.manifest_path)
                 .expect("loading manifest");
 
             debug!("upgrading {} from {} to {}", the_package.name, the_package.version, version);
@@ -84,16 +177,18 @@ impl VersionChanger for Cargo {
 
             // with single package, there's no need to update dependents
 
-            cargo_metadata(self.manifest_path.as_deref(), false);
+            vec![(the_package, version.clone())]
+        } else if !self.packages.is_empty() {
+            self.set_versions_of_packages(&workspace, &version)
         } else if let Some(name) = self.package.as_deref() {
             // name specified: set version of specified package in workspace
-            let the_package = ws_metadata.packages.iter().find(|p| name == p.name.as_ref())
+            let the_package = workspace.packages.iter().find(|p| name == p.name)
                 .expect("no package with specified name found");
 
-            let mut manifest = LocalManifest::try_new(Path::new(&the_package.manifest_path))
+            let mut manifest = LocalManifest::try_new(&the_package.manifest_path)
                 .expect("loading manifest");
             if manifest.version_is_inherited() {
-                panic!("to upgrade version name in workspace, don't specify package name");
+                panic!("to upgrade version name in workspace, don't specify package name. to version it separately, detach it with cargo-workspace-version --detach");
             };
 
             debug!("upgrading {} from {} to {}", the_package.name, the_package.version, version);
@@ -107,26 +202,27 @@ impl VersionChanger for Cargo {
             update_dependents(
                 &crate_root,
                 &version,
-                &root_manifest_path,
-                &ws_metadata.packages,
+                root_manifest_path,
+                &workspace.packages,
             );
 
-            cargo_metadata(self.manifest_path.as_deref(), false);
+            vec![(the_package, version.clone())]
         } else {
             // name not specified: set version of workspace
 
-            let mut ws_manifest = LocalManifest::try_new(&root_manifest_path)
+            let mut ws_manifest = LocalManifest::try_new(root_manifest_path)
                 .expect("loading manifest of workspace root");
 
-            if !ws_manifest.get_workspace_version().is_some() {
+            if ws_manifest.get_workspace_version().is_none() {
                 panic!("no workspace-wide version specified. to seet version of package in a workspace, specify package name");
             }
 
             ws_manifest.set_workspace_version(&version);
             ws_manifest.write().expect("writing manifest");
 
-            for package in &ws_metadata.packages {
-                let manifest = LocalManifest::try_new(Path::new(&package.manifest_path))
+            let mut updated = vec![];
+            for package in &workspace.packages {
+                let manifest = LocalManifest::try_new(&package.manifest_path)
                     .expect("loading manifest");
 
                 if manifest.version_is_inherited() {
@@ -136,14 +232,517 @@ impl VersionChanger for Cargo {
                     update_dependents(
                         &crate_root,
                         &version,
-                        &root_manifest_path,
-                        &ws_metadata.packages,
-                    )
+                        root_manifest_path,
+                        &workspace.packages,
+                    );
+                    updated.push((package, version.clone()));
+                }
+            }
+            updated
+        };
+
+        self.sync_lockfile(root_manifest_path, &updated);
+    }
+}
+
+impl Cargo {
+    pub(crate) fn new_for_packages(manifest_path: Option<PathBuf>, packages: Vec<String>) -> Self {
+        Self {
+            manifest_path,
+            package: None,
+            packages,
+            cascade: CascadeBump::Patch,
+            mode: CargoMode::Auto,
+        }
+    }
+
+    /// Converts the selected packages to `version.workspace = true`.
+    /// If the workspace has no version, the version of the packages is used.
+    pub(crate) fn inherit_workspace_version(&self) {
+        let workspace = self.load_workspace();
+        let root_manifest_path = &workspace.root_manifest_path;
+        let selected = self.selected_packages(&workspace);
+        let mut ws_manifest = LocalManifest::try_new(root_manifest_path)
+            .expect("loading manifest of workspace root");
+        if ws_manifest.data.get("workspace").is_none() {
+            panic!("{} is not a workspace", root_manifest_path.display());
+        }
+
+        let version = match ws_manifest.get_workspace_version() {
+            Some(version) => version,
+            None => {
+                let first = selected[0];
+                if let Some(other) = selected.iter().find(|p| p.version != first.version) {
+                    panic!("no workspace-wide version specified and versions of packages differ: {} is {} but {} is {}", first.name, first.version, other.name, other.version);
                 }
+                let version = semver::Version::parse(&first.version).expect("version name is not semver; cargo doesn't support non-semver");
+                let ws_table = ws_manifest.data["workspace"].as_table_like_mut()
+                    .expect("workspace is a table");
+                if !ws_table.contains_key("package") {
+                    // otherwise it would be an inline table
+                    ws_table.insert("package", toml_edit::Item::Table(toml_edit::Table::new()));
+                }
+                ws_manifest.set_workspace_version(&version);
+                ws_manifest.write().expect("writing manifest");
+                version
+            }
+        };
+
+        let mut updated = vec![];
+        for package in selected {
+            let mut manifest = LocalManifest::try_new(&package.manifest_path)
+                .expect("loading manifest");
+            if manifest.version_is_inherited() {
+                continue;
             }
+            manifest.data["package"]["version"] = inherited_version();
+            manifest.write().expect("writing manifest");
+
+            if package.version != version.to_string() {
+                info!("{}: {} -> {} (inherits workspace version)", package.name, package.version, version);
+                let crate_root =
+                    dunce::canonicalize(package.manifest_path.parent().expect("at least a parent"))
+                        .expect("canonicalize path");
+                update_dependents(
+                    &crate_root,
+                    &version,
+                    root_manifest_path,
+                    &workspace.packages,
+                );
+                updated.push((package, version.clone()));
+            }
+        }
+
+        self.sync_lockfile(root_manifest_path, &updated);
+    }
 
+    /// Converts the selected packages inheriting the workspace version to explicit versions.
+    /// If no package inherits the workspace version anymore, it's removed.
+    pub(crate) fn detach_workspace_version(&self) {
+        let workspace = self.load_workspace();
+        let root_manifest_path = &workspace.root_manifest_path;
+        let selected = self.selected_packages(&workspace);
+        let mut ws_manifest = LocalManifest::try_new(root_manifest_path)
+            .expect("loading manifest of workspace root");
+        let version = ws_manifest.get_workspace_version()
+            .expect("no workspace-wide version specified");
+
+        for package in &selected {
+            let mut manifest = LocalManifest::try_new(&package.manifest_path)
+                .expect("loading manifest");
+            if manifest.version_is_inherited() {
+                // the version doesn't change so dependents and Cargo.lock are kept
+                manifest.set_package_version(&version);
+                manifest.write().expect("writing manifest");
+            }
+        }
+
+        let any_inherited = workspace.packages.iter().any(|package| {
+            LocalManifest::try_new(&package.manifest_path)
+                .expect("loading manifest")
+                .version_is_inherited()
+        });
+        if !any_inherited {
+            // reload since the root may be one of the packages
+            ws_manifest = LocalManifest::try_new(root_manifest_path)
+                .expect("loading manifest of workspace root");
+            let ws_package = ws_manifest.data["workspace"]["package"].as_table_like_mut()
+                .expect("workspace package is a table");
+            ws_package.remove("version");
+            if ws_package.is_empty() {
+                ws_manifest.data["workspace"].as_table_like_mut()
+                    .expect("workspace is a table")
+                    .remove("package");
+            }
+            ws_manifest.write().expect("writing manifest");
+        }
+    }
+
+    fn sync_lockfile(&self, root_manifest_path: &Path, updated: &[(&Package, semver::Version)]) {
+        if self.use_metadata() {
+            // let cargo update Cargo.lock
             cargo_metadata(self.manifest_path.as_deref(), false);
+        } else {
+            let lockfile_path = root_manifest_path.with_file_name("Cargo.lock");
+            update_lockfile(&lockfile_path, updated);
+        }
+    }
+
+    fn selected_packages<'a>(&self, workspace: &'a Workspace) -> Vec<&'a Package> {
+        if self.package.is_some() {
+            panic!("package and packages cannot be specified at once");
+        }
+        let selected = workspace.packages.iter()
+            .filter(|p| self.packages.iter().any(|pattern| utils::glob::matches(pattern, &p.name)))
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            panic!("no package matched with {:?}", self.packages);
+        }
+        selected
+    }
+
+    /// Bumps the selected packages and dependents whose requirements break, in dependency order
+    fn set_versions_of_packages<'a>(&self, workspace: &'a Workspace, version: &semver::Version) -> Vec<(&'a Package, semver::Version)> {
+        let packages = &workspace.packages;
+        let dependencies = workspace_dependencies(workspace);
+
+        let mut next: Vec<Option<semver::Version>> = vec![None; packages.len()];
+        let mut reasons: Vec<String> = vec![String::new(); packages.len()];
+        for package in self.selected_packages(workspace) {
+            let index = packages.iter().position(|p| std::ptr::eq(p, package)).unwrap();
+            next[index] = Some(version.clone());
+            reasons[index] = "selected".to_owned();
+        }
+
+        // dev-dependencies are not published so they don't need re-release
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..packages.len() {
+                if next[index].is_some() {
+                    continue;
+                }
+                let broken = dependencies[index].iter()
+                    .filter(|dep| !dep.dev)
+                    .find(|dep| match (&next[dep.index], &dep.requirement) {
+                        (Some(next), Some(requirement)) => !semver::VersionReq::parse(requirement)
+                            .unwrap_or_else(|e| panic!("invalid requirement {requirement}: {e}"))
+                            .matches(next),
+                        _ => false,
+                    });
+                if let Some(broken) = broken {
+                    next[index] = Some(self.cascade.apply(&packages[index].version));
+                    reasons[index] = format!("requires {} {}", packages[broken.index].name, broken.requirement.as_deref().unwrap());
+                    changed = true;
+                }
+            }
+        }
+
+        // apply dependencies first
+        let mut order = vec![];
+        let mut remaining = (0..packages.len()).filter(|&i| next[i].is_some()).collect::<Vec<_>>();
+        while !remaining.is_empty() {
+            let ready = remaining.iter().copied()
+                .filter(|&i| dependencies[i].iter().all(|dep| dep.dev || !remaining.contains(&dep.index) || dep.index == i))
+                .collect::<Vec<_>>();
+            if ready.is_empty() {
+                panic!("dependency cycle among {:?}", remaining.iter().map(|&i| &packages[i].name).collect::<Vec<_>>());
+            }
+            remaining.retain(|i| !ready.contains(i));
+            order.extend(ready);
+        }
+
+        // validate all manifests before writing anything not to leave the workspace half-bumped
+        for &index in &order {
+            let package = &packages[index];
+            let manifest = LocalManifest::try_new(&package.manifest_path)
+                .expect("loading manifest");
+            if manifest.version_is_inherited() {
+                panic!("{} inherits workspace version; it cannot be versioned independently ({})", package.name, reasons[index]);
+            }
+        }
+
+        let mut updated = vec![];
+        for index in order {
+            let package = &packages[index];
+            let next = next[index].take().unwrap();
+            // dependents may have been updated by former packages so load here
+            let mut manifest = LocalManifest::try_new(&package.manifest_path)
+                .expect("loading manifest");
+
+            info!("{}: {} -> {} ({})", package.name, package.version, next, reasons[index]);
+            manifest.set_package_version(&next);
+            manifest.write().expect("writing manifest");
+
+            let crate_root =
+                dunce::canonicalize(package.manifest_path.parent().expect("at least a parent"))
+                    .expect("canonicalize path");
+            update_dependents(
+                &crate_root,
+                &next,
+                &workspace.root_manifest_path,
+                packages,
+            );
+            updated.push((package, next));
         }
+        updated
+    }
+
+    fn use_metadata(&self) -> bool {
+        match self.mode {
+            CargoMode::Auto => cargo_available(),
+            CargoMode::Metadata => true,
+            CargoMode::Manifest => false,
+        }
+    }
+
+    fn load_workspace(&self) -> Workspace {
+        if self.use_metadata() {
+            let ws_metadata = cargo_metadata(self.manifest_path.as_deref(), true);
+            Workspace {
+                root_manifest_path: ws_metadata.workspace_root.as_std_path().join("Cargo.toml"),
+                packages: ws_metadata.packages.into_iter()
+                    .map(|p| Package {
+                        name: p.name.to_string(),
+                        version: p.version.to_string(),
+                        manifest_path: p.manifest_path.into_std_path_buf(),
+                    })
+                    .collect(),
+            }
+        } else {
+            load_workspace_from_manifests(self.manifest_path.as_deref())
+        }
+    }
+}
+
+/// `version.workspace = true`
+fn inherited_version() -> toml_edit::Item {
+    let mut table = toml_edit::InlineTable::new();
+    table.insert("workspace", true.into());
+    table.set_dotted(true);
+    toml_edit::Item::Value(table.into())
+}
+
+/// Checks `cargo --version` once per process
+fn cargo_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        std::process::Command::new(cargo)
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    })
+}
+
+fn read_manifest(path: &Path) -> toml_edit::DocumentMut {
+    std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e))
+        .parse()
+        .unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e))
+}
+
+/// Resolves the workspace like cargo does without invoking cargo
+fn load_workspace_from_manifests(manifest_path: Option<&Path>) -> Workspace {
+    let manifest_path = match manifest_path {
+        Some(path) => path.to_owned(),
+        None => {
+            let cwd = std::env::current_dir().expect("getting current directory");
+            cwd.ancestors()
+                .map(|dir| dir.join("Cargo.toml"))
+                .find(|path| path.is_file())
+                .expect("could not find Cargo.toml in current directory or any parent directory")
+        }
+    };
+    let manifest_path = dunce::canonicalize(&manifest_path)
+        .unwrap_or_else(|e| panic!("canonicalize {}: {}", manifest_path.display(), e));
+    let root_manifest_path = find_workspace_root(&manifest_path);
+    let root = read_manifest(&root_manifest_path);
+    let root_dir = root_manifest_path.parent().expect("at least a parent");
+
+    let workspace_version = root.get("workspace")
+        .and_then(|w| w.get("package"))
+        .and_then(|p| p.get("version"))
+        .and_then(|v| v.as_str());
+
+    let mut manifest_paths = vec![];
+    if root.contains_key("package") {
+        manifest_paths.push(root_manifest_path.clone());
+    }
+    if let Some(workspace) = root.get("workspace") {
+        for member in workspace_members(workspace, root_dir) {
+            let path = root_dir.join(member).join("Cargo.toml");
+            if !manifest_paths.contains(&path) {
+                manifest_paths.push(path);
+            }
+        }
+    }
+
+    let packages = manifest_paths.into_iter()
+        .map(|manifest_path| {
+            let manifest = read_manifest(&manifest_path);
+            let package = manifest.get("package")
+                .unwrap_or_else(|| panic!("no package in {}", manifest_path.display()));
+            let name = package.get("name").and_then(|x| x.as_str())
+                .unwrap_or_else(|| panic!("no package name in {}", manifest_path.display()));
+            let version = match package.get("version") {
+                // since cargo 1.75, version defaults to 0.0.0
+                None => "0.0.0",
+                Some(version) if version.get("workspace").and_then(|x| x.as_bool()) == Some(true) => {
+                    workspace_version
+                        .unwrap_or_else(|| panic!("{} inherits version but no workspace-wide version specified", manifest_path.display()))
+                }
+                Some(version) => version.as_str()
+                    .unwrap_or_else(|| panic!("package version in {} is not a string", manifest_path.display())),
+            };
+            Package {
+                name: name.to_owned(),
+                version: version.to_owned(),
+                manifest_path,
+            }
+        })
+        .collect();
+
+    Workspace {
+        root_manifest_path,
+        packages,
+    }
+}
+
+/// Returns the relative paths of the members of workspace
+fn workspace_members(workspace: &toml_edit::Item, root_dir: &Path) -> Vec<String> {
+    let strings = |key: &str| -> Vec<String> {
+        workspace.get(key)
+            .and_then(|x| x.as_array())
+            .map(|array| array.iter().filter_map(|x| x.as_str()).map(|x| x.to_owned()).collect())
+            .unwrap_or_default()
+    };
+    let exclude = strings("exclude");
+    strings("members").iter()
+        .flat_map(|pattern| utils::glob::expand(root_dir, pattern))
+        .filter(|member| root_dir.join(member).join("Cargo.toml").is_file())
+        .filter(|member| !exclude.iter().any(|excluded| Path::new(member).starts_with(excluded)))
+        .collect()
+}
+
+/// Finds the manifest of the workspace root the package belongs to
+fn find_workspace_root(manifest_path: &Path) -> PathBuf {
+    let manifest = read_manifest(manifest_path);
+    if manifest.contains_key("workspace") {
+        return manifest_path.to_owned();
+    }
+    let package_dir = manifest_path.parent().expect("at least a parent");
+    if let Some(root) = manifest.get("package").and_then(|p| p.get("workspace")).and_then(|x| x.as_str()) {
+        let root = package_dir.join(root).join("Cargo.toml");
+        return dunce::canonicalize(&root)
+            .unwrap_or_else(|e| panic!("canonicalize {}: {}", root.display(), e));
+    }
+
+    for dir in package_dir.ancestors().skip(1) {
+        let candidate = dir.join("Cargo.toml");
+        if !candidate.is_file() {
+            continue;
+        }
+        let Some(workspace) = read_manifest(&candidate).remove("workspace") else {
+            continue;
+        };
+        let relative = package_dir.strip_prefix(dir).unwrap().to_str().expect("non utf-8 path");
+        let relative = relative.replace(std::path::MAIN_SEPARATOR, "/");
+        if workspace_members(&workspace, dir).contains(&relative) {
+            return candidate;
+        }
+    }
+
+    // the package is not a member of any workspace
+    manifest_path.to_owned()
+}
+
+/// a dependency on another package in the workspace
+struct WorkspaceDependency {
+    /// the index of the package depended on
+    index: usize,
+    requirement: Option<String>,
+    dev: bool,
+}
+
+/// Returns the dependencies on the workspace packages for each package
+fn workspace_dependencies(workspace: &Workspace) -> Vec<Vec<WorkspaceDependency>> {
+    let root = read_manifest(&workspace.root_manifest_path);
+    let root_dir = workspace.root_manifest_path.parent().expect("at least a parent");
+    let workspace_deps = root.get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|d| d.as_table_like());
+    let crate_roots = workspace.packages.iter()
+        .map(|p| dunce::canonicalize(p.manifest_path.parent().expect("at least a parent")).expect("canonicalize path"))
+        .collect::<Vec<_>>();
+    let find = |base: &Path, dep: &dyn toml_edit::TableLike| {
+        let path = dunce::canonicalize(base.join(dep.get("path")?.as_str()?)).ok()?;
+        crate_roots.iter().position(|root| *root == path)
+    };
+
+    workspace.packages.iter()
+        .map(|package| {
+            let manifest = read_manifest(&package.manifest_path);
+            let dir = package.manifest_path.parent().expect("at least a parent");
+            let mut found = vec![];
+            for (table, dev) in dependency_tables(&manifest) {
+                for (key, dep) in table.iter() {
+                    let Some(dep) = dep.as_table_like() else {
+                        continue;
+                    };
+                    let (base, dep) = if dep.get("workspace").and_then(|x| x.as_bool()) == Some(true) {
+                        match workspace_deps.and_then(|w| w.get(key)).and_then(|x| x.as_table_like()) {
+                            Some(dep) => (root_dir, dep),
+                            None => continue,
+                        }
+                    } else {
+                        (dir, dep)
+                    };
+                    if let Some(index) = find(base, dep) {
+                        let requirement = dep.get("version").and_then(|x| x.as_str()).map(Into::into);
+                        found.push(WorkspaceDependency { index, requirement, dev });
+                    }
+                }
+            }
+            found
+        })
+        .collect()
+}
+
+/// Returns the dependency tables including `[target.*]` ones with whether it's dev-dependencies
+fn dependency_tables(manifest: &toml_edit::DocumentMut) -> Vec<(&dyn toml_edit::TableLike, bool)> {
+    fn add<'a>(parent: &'a dyn toml_edit::TableLike, tables: &mut Vec<(&'a dyn toml_edit::TableLike, bool)>) {
+        for (kind, dev) in [("dependencies", false), ("build-dependencies", false), ("dev-dependencies", true)] {
+            if let Some(table) = parent.get(kind).and_then(|x| x.as_table_like()) {
+                tables.push((table, dev));
+            }
+        }
+    }
+
+    let mut tables = vec![];
+    add(manifest.as_table(), &mut tables);
+    if let Some(targets) = manifest.get("target").and_then(|x| x.as_table_like()) {
+        for (_, target) in targets.iter() {
+            if let Some(target) = target.as_table_like() {
+                add(target, &mut tables);
+            }
+        }
+    }
+    tables
+}
+
+/// Sets the version of workspace packages in Cargo.lock
+fn update_lockfile(lockfile_path: &Path, updated: &[(&Package, semver::Version)]) {
+    let Ok(source) = std::fs::read_to_string(lockfile_path) else {
+        debug!("{} not found; skipping updating lockfile", lockfile_path.display());
+        return;
+    };
+    let mut lockfile = source.parse::<toml_edit::DocumentMut>()
+        .unwrap_or_else(|e| panic!("parsing {}: {}", lockfile_path.display(), e));
+
+    let Some(packages) = lockfile.get_mut("package").and_then(|x| x.as_array_of_tables_mut()) else {
+        return;
+    };
+    let mut changed = false;
+    for entry in packages.iter_mut() {
+        // packages in workspace have no source
+        if entry.contains_key("source") {
+            continue;
+        }
+        let name = entry.get("name").and_then(|x| x.as_str());
+        let version = entry.get("version").and_then(|x| x.as_str());
+        let Some((_, next)) = updated.iter().find(|(p, _)| Some(p.name.as_str()) == name && Some(p.version.as_str()) == version) else {
+            continue;
+        };
+        if let Some(value) = entry.get_mut("version").and_then(|x| x.as_value_mut()) {
+            utils::toml::set_string(value, &next.to_string());
+            changed = true;
+        }
+    }
+
+    if changed {
+        std::fs::write(lockfile_path, lockfile.to_string())
+            .unwrap_or_else(|e| panic!("writing {}: {}", lockfile_path.display(), e));
     }
 }
 
@@ -151,7 +750,7 @@ fn update_dependents(
     crate_root: &Path,
     next: &semver::Version,
     root_manifest_path: &Path,
-    workspace_members: &[cargo_metadata::Package],
+    workspace_members: &[Package],
 ) {
     // This is redundant with iterating over `workspace_members`
     // - As `get_dependency_tables_mut` returns workspace dependencies
@@ -168,7 +767,7 @@ fn update_dependents(
         update_dependent(
             crate_root,
             next,
-            member.manifest_path.as_std_path(),
+            &member.manifest_path,
             &member.name,
         );
     }
@@ -202,10 +801,29 @@ fn update_dependent(
         .expect("at least a parent")
         .to_owned();
 
-    for dep in dep_manifest
-        .get_dependency_tables_mut()
+    changed |= update_requirements(dep_manifest.get_dependency_tables_mut(), crate_root, &dep_crate_root, next, name);
+
+    // [patch.<registry>] tables are not included in dependency tables
+    if let Some(patch) = dep_manifest.data.get_mut("patch").and_then(|x| x.as_table_like_mut()) {
+        let tables = patch.iter_mut().filter_map(|(_, t)| t.as_table_like_mut());
+        changed |= update_requirements(tables, crate_root, &dep_crate_root, next, name);
+    }
+    if changed {
+        dep_manifest.write().expect("writing Cargo.toml")
+    }
+}
+
+fn update_requirements<'a>(
+    tables: impl Iterator<Item = &'a mut dyn toml_edit::TableLike>,
+    crate_root: &Path,
+    dep_crate_root: &Path,
+    next: &semver::Version,
+    name: &str,
+) -> bool {
+    let mut changed = false;
+    for dep in tables
         .flat_map(|t| t.iter_mut().filter_map(|(_, d)| d.as_table_like_mut()))
-        .filter(|d| is_relevant(*d, &dep_crate_root, crate_root))
+        .filter(|d| is_relevant(*d, dep_crate_root, crate_root))
     {
         let old_req = dep
             .get("version")
@@ -218,9 +836,7 @@ fn update_dependent(
             changed = true;
         }
     }
-    if changed {
-        dep_manifest.write().expect("writing Cargo.toml")
-    }
+    changed
 }
 
 fn cargo_metadata(manifest_path: Option<&Path>, no_deps: bool) -> cargo_metadata::Metadata {
@@ -234,3 +850,125 @@ fn cargo_metadata(manifest_path: Option<&Path>, no_deps: bool) -> cargo_metadata
     }
     cmd.exec().expect("cargo metadata failed")
 }
+
+#[tokio::test]
+async fn manifest_mode() {
+    let dir = super::copy_fixture("cargo/manifest-mode");
+    let read = |path: &str| std::fs::read_to_string(dir.path().join(path)).unwrap();
+    let lock = read("Cargo.lock");
+    let excluded = read("crates/excluded/Cargo.toml");
+    let changer = |package: Option<&str>, manifest: &str| Cargo {
+        manifest_path: Some(dir.path().join(manifest)),
+        package: package.map(Into::into),
+        packages: vec![],
+        cascade: CascadeBump::Patch,
+        mode: CargoMode::Manifest,
+    };
+
+    let workspace = load_workspace_from_manifests(Some(&dir.path().join("crates/core/Cargo.toml")));
+    assert_eq!(workspace.root_manifest_path, dunce::canonicalize(dir.path().join("Cargo.toml")).unwrap());
+    let mut names = workspace.packages.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["cli", "core"]);
+
+    let workspace_changer = changer(None, "Cargo.toml");
+    assert_eq!(workspace_changer.load_version().await, "1.0.0");
+    workspace_changer.set_version("1.1.0").await;
+    assert_eq!(workspace_changer.load_version().await, "1.1.0");
+    assert!(read("crates/cli/Cargo.toml").contains("core = { path = \"../core\", version = \"1.1.0\" }"));
+
+    let cli = changer(Some("cli"), "crates/cli/Cargo.toml");
+    assert_eq!(cli.load_version().await, "0.1.0");
+    cli.set_version("0.2.0").await;
+    assert_eq!(cli.load_version().await, "0.2.0");
+
+    assert_eq!(
+        read("Cargo.lock"),
+        lock
+            .replace("name = \"cli\"\nversion = \"0.1.0\"", "name = \"cli\"\nversion = \"0.2.0\"")
+            .replace("name = \"core\"\nversion = \"1.0.0\"", "name = \"core\"\nversion = \"1.1.0\""),
+    );
+    assert_eq!(read("crates/excluded/Cargo.toml"), excluded);
+}
+
+#[tokio::test]
+async fn cascade_to_dependents() {
+    let dir = super::copy_fixture("cargo/cascade");
+    let read = |path: &str| std::fs::read_to_string(dir.path().join(path)).unwrap();
+    let root = read("Cargo.toml");
+    let c = read("crates/c/Cargo.toml");
+    let e = read("crates/e/Cargo.toml");
+
+    let changer = Cargo {
+        manifest_path: Some(dir.path().join("Cargo.toml")),
+        package: None,
+        packages: vec!["a".to_owned()],
+        cascade: CascadeBump::Patch,
+        mode: CargoMode::Manifest,
+    };
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("2.0.0").await;
+    assert_eq!(changer.load_version().await, "2.0.0");
+
+    assert_eq!(read("Cargo.toml"), root.replace("version = \"1.0.0\"", "version = \"2.0.0\""));
+    assert_eq!(read("crates/b/Cargo.toml"), "[package]\nname = \"b\"\nversion = \"1.0.1\"\n\n[dependencies]\na = { path = \"../a\", version = \"2.0.0\" }\n");
+    // b 1.0.1 still matches with 1.0
+    assert_eq!(read("crates/c/Cargo.toml"), c);
+    assert_eq!(read("crates/d/Cargo.toml"), "[package]\nname = \"d\"\nversion = \"0.1.1\"\n\n[target.'cfg(unix)'.dependencies]\nb = { path = \"../b\", version = \"=1.0.1\" }\n");
+    // dev-dependencies are updated but not re-released
+    assert_eq!(read("crates/e/Cargo.toml"), e.replace("version = \"1.0\" }", "version = \"2.0\" }"));
+}
+
+#[tokio::test]
+async fn cascade_to_inheriting_dependent() {
+    let dir = super::copy_fixture("cargo/cascade-inheriting");
+    let paths = ["Cargo.toml", "crates/a/Cargo.toml", "crates/b/Cargo.toml"];
+    let read = |path: &str| std::fs::read_to_string(dir.path().join(path)).unwrap();
+    let files = paths.map(|path| (path, read(path)));
+
+    let changer = Cargo {
+        manifest_path: Some(dir.path().join("Cargo.toml")),
+        package: None,
+        packages: vec!["a".to_owned()],
+        cascade: CascadeBump::Patch,
+        mode: CargoMode::Manifest,
+    };
+    let result = tokio::spawn(async move { changer.set_version("2.0.0").await }).await;
+    assert!(result.is_err());
+    // nothing is written
+    for (path, content) in files {
+        assert_eq!(read(path), content);
+    }
+}
+
+#[tokio::test]
+async fn workspace_inheritance() {
+    let dir = super::copy_fixture("cargo/workspace-inheritance");
+    let read = |path: &str| std::fs::read_to_string(dir.path().join(path)).unwrap();
+    let root = read("Cargo.toml");
+    let b = read("crates/b/Cargo.toml");
+    let c = read("crates/c/Cargo.toml");
+    let changer = |packages: &[&str]| {
+        let mut changer = Cargo::new_for_packages(
+            Some(dir.path().join("Cargo.toml")),
+            packages.iter().map(|x| x.to_string()).collect(),
+        );
+        changer.mode = CargoMode::Manifest;
+        changer
+    };
+
+    changer(&["a", "b"]).inherit_workspace_version();
+    assert_eq!(read("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nversion = \"1.0.0\"\n");
+    assert_eq!(read("crates/a/Cargo.toml"), "[package]\nname = \"a\"\nversion.workspace = true\nedition = \"2021\"\n");
+
+    // c is moved to the workspace version and dependents are kept consistent
+    changer(&["c"]).inherit_workspace_version();
+    assert_eq!(read("crates/c/Cargo.toml"), c.replace("version = \"0.1.0\"", "version.workspace = true"));
+
+    changer(&["b"]).detach_workspace_version();
+    assert_eq!(read("crates/b/Cargo.toml"), b);
+
+    changer(&["*"]).detach_workspace_version();
+    assert_eq!(read("Cargo.toml"), root);
+    assert_eq!(read("crates/c/Cargo.toml"), c.replace("0.1.0", "1.0.0"));
+}
@@ -0,0 +1,267 @@
+//! The string representation of version changers used by `RELEASE_CHANGER` environment variables
+//!
+//! The format is `kind[:info][@path]`, and multiple changers are separated with `;`.
+//!
+//! Each part can be quoted with `"` when it contains a character which would end the part
+//! (`:`, `@` or `;` for kind, `@` or `;` for info, and `;` for path).
+//! In quoted part, `\"` and `\\` are unescaped to `"` and `\`, and other backslashes are kept as is.
+//!
+//! For compatibility with the unquoted format, a quoted part is only recognized if
+//! the closing quote is followed by end of the part and the unquoted value is empty or contains
+//! at least one character which requires quoting.
+//! Otherwise, the quotes are part of the value as before.
+
+#[derive(Debug, Eq, PartialEq)]
+pub(super) struct ChangerSpec {
+    pub kind: String,
+    pub info: Option<String>,
+    pub path: Option<String>,
+}
+
+const KIND_DELIMITERS: &[char] = &[':', '@', ';'];
+const INFO_DELIMITERS: &[char] = &['@', ';'];
+const PATH_DELIMITERS: &[char] = &[';'];
+
+pub(super) fn parse_changers(s: &str) -> Vec<ChangerSpec> {
+    let mut rest = s;
+    let mut changers = Vec::new();
+    loop {
+        let (kind, next) = read_part(rest, KIND_DELIMITERS);
+        rest = next;
+
+        let mut info = None;
+        if let Some(next) = rest.strip_prefix(':') {
+            let (value, next) = read_part(next, INFO_DELIMITERS);
+            info = Some(value);
+            rest = next;
+        }
+
+        let mut path = None;
+        if let Some(next) = rest.strip_prefix('@') {
+            let (value, next) = read_part(next, PATH_DELIMITERS);
+            path = Some(value);
+            rest = next;
+        }
+
+        changers.push(ChangerSpec {
+            kind: kind.value,
+            info: info.and_then(Part::into_option),
+            path: path.and_then(Part::into_option),
+        });
+
+        match rest.strip_prefix(';') {
+            Some(next) => rest = next,
+            None => {
+                debug_assert!(rest.is_empty());
+                break changers;
+            }
+        }
+    }
+}
+
+struct Part {
+    value: String,
+    quoted: bool,
+}
+
+impl Part {
+    /// unquoted empty part is same as missing part
+    fn into_option(self) -> Option<String> {
+        if self.value.is_empty() && !self.quoted {
+            None
+        } else {
+            Some(self.value)
+        }
+    }
+}
+
+fn read_part<'a>(s: &'a str, delimiters: &[char]) -> (Part, &'a str) {
+    if let Some((value, rest)) = read_quoted(s) {
+        if (rest.is_empty() || rest.starts_with(delimiters)) && needs_quote(&value, delimiters) {
+            let part = Part {
+                value,
+                quoted: true,
+            };
+            return (part, rest);
+        }
+    }
+
+    let end = s.find(delimiters).unwrap_or(s.len());
+    let part = Part {
+        value: s[..end].to_owned(),
+        quoted: false,
+    };
+    (part, &s[end..])
+}
+
+fn read_quoted(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, chars.as_str())),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => value.push(c),
+                Some((_, c)) => {
+                    value.push('\\');
+                    value.push(c);
+                }
+                None => return None,
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Returns the canonical string representation of the changer
+pub(crate) fn changer_string(kind: &str, info: Option<&str>, path: Option<&str>) -> String {
+    let mut builder = encode_part(kind, KIND_DELIMITERS);
+    if let Some(info) = info {
+        builder.push(':');
+        builder.push_str(&encode_part(info, INFO_DELIMITERS));
+    }
+    if let Some(path) = path {
+        builder.push('@');
+        builder.push_str(&encode_part(path, PATH_DELIMITERS));
+    }
+    builder
+}
+
+/// values starting with `"` are quoted so that they are not read as quoted values
+fn needs_quote(value: &str, delimiters: &[char]) -> bool {
+    value.is_empty() || value.starts_with('"') || value.contains(delimiters)
+}
+
+fn encode_part(value: &str, delimiters: &[char]) -> String {
+    if needs_quote(value, delimiters) {
+        escapes!(
+            value,
+            @prefix = "\"",
+            @suffix = "\"",
+            '"' => "\\\"",
+            '\\' => "\\\\",
+        )
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+fn spec(kind: &str, info: Option<&str>, path: Option<&str>) -> ChangerSpec {
+    ChangerSpec {
+        kind: kind.to_owned(),
+        info: info.map(ToOwned::to_owned),
+        path: path.map(ToOwned::to_owned),
+    }
+}
+
+#[test]
+fn parse_unquoted() {
+    assert_eq!(parse_changers("npm"), vec![spec("npm", None, None)]);
+    assert_eq!(
+        parse_changers("gradle-properties:version@gradle.properties;npm@package.json"),
+        vec![
+            spec(
+                "gradle-properties",
+                Some("version"),
+                Some("gradle.properties")
+            ),
+            spec("npm", None, Some("package.json")),
+        ]
+    );
+    assert_eq!(
+        parse_changers(r#"regex-pattern:version: "$1"@build.gradle"#),
+        vec![spec(
+            "regex-pattern",
+            Some(r#"version: "$1""#),
+            Some("build.gradle")
+        )]
+    );
+    assert_eq!(
+        parse_changers("cargo:@Cargo.toml"),
+        vec![spec("cargo", None, Some("Cargo.toml"))]
+    );
+}
+
+#[test]
+fn parse_quoted() {
+    assert_eq!(
+        parse_changers(r#"regex-pattern:"\"@scope/pkg@$1\""@package.json;npm"#),
+        vec![
+            spec(
+                "regex-pattern",
+                Some(r#""@scope/pkg@$1""#),
+                Some("package.json")
+            ),
+            spec("npm", None, None),
+        ]
+    );
+    assert_eq!(
+        parse_changers(r#"regex-pattern:"a;\d+$1"@"dir;name/file""#),
+        vec![spec(
+            "regex-pattern",
+            Some(r"a;\d+$1"),
+            Some("dir;name/file")
+        )]
+    );
+    assert_eq!(
+        parse_changers(r#"cargo:""@Cargo.toml"#),
+        vec![spec("cargo", Some(""), Some("Cargo.toml"))]
+    );
+}
+
+#[test]
+fn parse_quote_compatibility() {
+    // quoted values without special characters are kept as is
+    assert_eq!(
+        parse_changers(r#"regex-pattern:"$1"@version.json"#),
+        vec![spec("regex-pattern", Some(r#""$1""#), Some("version.json"))]
+    );
+    // quote not followed by delimiter is kept as is
+    assert_eq!(
+        parse_changers(r#"regex-pattern:"version": "$1"@package.json"#),
+        vec![spec(
+            "regex-pattern",
+            Some(r#""version": "$1""#),
+            Some("package.json")
+        )]
+    );
+}
+
+#[test]
+fn canonical_round_trip() {
+    fn round_trip(kind: &str, info: Option<&str>, path: Option<&str>) {
+        let encoded = changer_string(kind, info, path);
+        assert_eq!(
+            parse_changers(&encoded),
+            vec![spec(kind, info, path)],
+            "{encoded}"
+        );
+    }
+
+    round_trip("npm", None, None);
+    round_trip(
+        "regex-pattern",
+        Some(r#""version": "$1""#),
+        Some("package.json"),
+    );
+    round_trip(
+        "regex-pattern",
+        Some(r#""@scope/pkg@$1""#),
+        Some("package.json"),
+    );
+    round_trip("regex-pattern", Some(r#"\"a;b"\"#), Some("a;b"));
+    round_trip("cargo", Some(""), None);
+    round_trip("regex-pattern", Some(r#""""#), Some(r#""a""#));
+    round_trip("regex-pattern", Some(r#""$1""#), None);
+
+    assert_eq!(
+        changer_string("npm", None, Some("package.json")),
+        "npm@package.json"
+    );
+    assert_eq!(
+        changer_string("regex-pattern", Some("a@$1"), Some("f")),
+        r#"regex-pattern:"a@$1"@f"#
+    );
+}
@@ -0,0 +1,302 @@
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Sets the `VERSION` argument of `project()` command in `CMakeLists.txt`
+#[derive(Debug, Deserialize)]
+pub(crate) struct Cmake {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    #[serde(default, alias = "info")]
+    prerelease: PrereleaseMode,
+}
+
+/// CMake only allows `major[.minor[.patch[.tweak]]]` for the project version
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum PrereleaseMode {
+    /// panics for versions with prerelease or build metadata
+    #[default]
+    #[serde(rename = "error")]
+    Error,
+    /// removes prerelease and build metadata: `1.2.0-rc.1` is written as `1.2.0`
+    #[serde(rename = "strip")]
+    Strip,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("CMakeLists.txt")
+}
+
+impl Display for Cmake {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cmake(at {})", self.path.display())
+    }
+}
+
+impl Cmake {
+    async fn read(&self) -> String {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", self.path.display(), e))
+    }
+
+    /// Returns the range of the version argument excluding quotes
+    fn find_version(&self, source: &str) -> Range<usize> {
+        let arguments = commands(source)
+            .unwrap_or_else(|e| panic!("parsing {}: {}", self.path.display(), e))
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("project"))
+            .unwrap_or_else(|| panic!("no project() in {}", self.path.display()))
+            .1;
+        let keyword = arguments
+            .iter()
+            .position(|x| x.kind == ArgumentKind::Unquoted && &source[x.value.clone()] == "VERSION")
+            .unwrap_or_else(|| panic!("no VERSION in project() of {}", self.path.display()));
+        let version = arguments
+            .get(keyword + 1)
+            .unwrap_or_else(|| panic!("no value for VERSION in {}", self.path.display()));
+        if source[version.value.clone()].contains("${") {
+            panic!(
+                "VERSION in {} refers variable: {}",
+                self.path.display(),
+                &source[version.value.clone()]
+            );
+        }
+        version.value.clone()
+    }
+}
+
+impl VersionChanger for Cmake {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let prerelease = match info {
+            None | Some("error") => PrereleaseMode::Error,
+            Some("strip") => PrereleaseMode::Strip,
+            Some(info) => panic!("invalid cmake version changer: unknown prerelease mode {info}"),
+        };
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            prerelease,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = self.read().await;
+        source[self.find_version(&source)].to_owned()
+    }
+
+    async fn set_version(&self, version: &str) {
+        let version = match self.prerelease {
+            PrereleaseMode::Error => version,
+            PrereleaseMode::Strip => version.split(['-', '+']).next().unwrap(),
+        };
+        let parts = version.split('.').collect::<Vec<_>>();
+        let numeric = |x: &&str| !x.is_empty() && x.bytes().all(|x| x.is_ascii_digit());
+        if parts.len() > 4 || !parts.iter().all(numeric) {
+            panic!("cmake project version must be numeric like 1.2.3: {version}");
+        }
+
+        let mut source = self.read().await;
+        let range = self.find_version(&source);
+        source.replace_range(range, version);
+        tokio::fs::write(&self.path, source)
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum ArgumentKind {
+    Unquoted,
+    Quoted,
+    Bracket,
+}
+
+#[derive(Debug)]
+struct Argument {
+    kind: ArgumentKind,
+    /// range of the content without quotes or brackets
+    value: Range<usize>,
+}
+
+/// Parses the command invocations in the cmake source, returning names and arguments
+fn commands(source: &str) -> Result<Vec<(&str, Vec<Argument>)>, String> {
+    let bytes = source.as_bytes();
+    let mut pos = 0;
+    let mut commands = Vec::new();
+
+    loop {
+        pos = skip_space_and_comments(source, pos)?;
+        if pos == bytes.len() {
+            return Ok(commands);
+        }
+        let start = pos;
+        while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(format!("expected command at byte {pos}"));
+        }
+        let name = &source[start..pos];
+        while pos < bytes.len() && matches!(bytes[pos], b' ' | b'\t') {
+            pos += 1;
+        }
+        if bytes.get(pos) != Some(&b'(') {
+            return Err(format!("expected ( after {name}"));
+        }
+        pos += 1;
+
+        let mut arguments = Vec::new();
+        // unquoted parentheses are allowed in arguments if balanced
+        let mut depth = 0;
+        loop {
+            pos = skip_space_and_comments(source, pos)?;
+            match bytes.get(pos) {
+                None => return Err(format!("unclosed {name}(")),
+                Some(b')') if depth == 0 => {
+                    pos += 1;
+                    break;
+                }
+                Some(b'(') => {
+                    depth += 1;
+                    pos += 1;
+                }
+                Some(b')') => {
+                    depth -= 1;
+                    pos += 1;
+                }
+                Some(b'"') => {
+                    let start = pos + 1;
+                    pos = start;
+                    loop {
+                        match bytes.get(pos) {
+                            None => return Err("unclosed quoted argument".to_owned()),
+                            Some(b'\\') => pos += 2,
+                            Some(b'"') => break,
+                            Some(_) => pos += 1,
+                        }
+                    }
+                    arguments.push(Argument {
+                        kind: ArgumentKind::Quoted,
+                        value: start..pos,
+                    });
+                    pos += 1;
+                }
+                Some(b'[') if bracket_open(&source[pos..]).is_some() => {
+                    let (value, end) = bracket(source, pos)?;
+                    arguments.push(Argument {
+                        kind: ArgumentKind::Bracket,
+                        value,
+                    });
+                    pos = end;
+                }
+                Some(_) => {
+                    let start = pos;
+                    while let Some(&c) = bytes.get(pos) {
+                        match c {
+                            b'\\' => pos += 2,
+                            b' ' | b'\t' | b'\r' | b'\n' | b'(' | b')' | b'#' | b'"' => break,
+                            _ => pos += 1,
+                        }
+                    }
+                    arguments.push(Argument {
+                        kind: ArgumentKind::Unquoted,
+                        value: start..pos.min(bytes.len()),
+                    });
+                }
+            }
+        }
+        commands.push((name, arguments));
+    }
+}
+
+/// Returns the number of `=` if the source starts with bracket open like `[==[`
+fn bracket_open(source: &str) -> Option<usize> {
+    let rest = source.strip_prefix('[')?;
+    let equals = rest.bytes().take_while(|&x| x == b'=').count();
+    (rest.as_bytes().get(equals) == Some(&b'[')).then_some(equals)
+}
+
+/// Returns the range of the bracket content and the end of the closing bracket
+fn bracket(source: &str, start: usize) -> Result<(Range<usize>, usize), String> {
+    let equals = bracket_open(&source[start..]).unwrap();
+    let content = start + equals + 2;
+    let close = format!("]{}]", "=".repeat(equals));
+    let end = source[content..].find(&close).ok_or("unclosed bracket")? + content;
+    Ok((content..end, end + close.len()))
+}
+
+fn skip_space_and_comments(source: &str, mut pos: usize) -> Result<usize, String> {
+    let bytes = source.as_bytes();
+    while let Some(&c) = bytes.get(pos) {
+        if c.is_ascii_whitespace() {
+            pos += 1;
+        } else if c == b'#' {
+            if bracket_open(&source[pos + 1..]).is_some() {
+                pos = bracket(source, pos + 1)?.1;
+            } else {
+                pos = source[pos..].find('\n').map_or(bytes.len(), |x| pos + x);
+            }
+        } else {
+            break;
+        }
+    }
+    Ok(pos)
+}
+
+#[test]
+fn commands_test() {
+    let source = r#"cmake_minimum_required(VERSION 3.16) # VERSION 0.0.0
+#[[ project(commented VERSION 0.0.0) ]]
+if((A OR B) AND "x)")
+endif()
+set(DOC [==[ ) ]] ]==])
+"#;
+    let commands = commands(source).unwrap();
+    let names = commands.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+    assert_eq!(names, ["cmake_minimum_required", "if", "endif", "set"]);
+    let values = |i: usize| {
+        commands[i]
+            .1
+            .iter()
+            .map(|x| &source[x.value.clone()])
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(values(0), ["VERSION", "3.16"]);
+    assert_eq!(values(1), ["A", "OR", "B", "AND", "x)"]);
+    assert_eq!(values(3), ["DOC", " ) ]] "]);
+}
+
+#[tokio::test]
+async fn cmake_lists() {
+    let dir = super::copy_fixture("cmake");
+    let path = dir.path().join("comments/CMakeLists.txt");
+    let source = std::fs::read_to_string(&path).unwrap();
+    let path = path.to_str();
+
+    let changer = Cmake::parse(Some("strip"), path);
+    assert_eq!(changer.load_version().await, "1.2.3");
+    changer.set_version("1.3.0-rc.1").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        source.replace("\"1.2.3\"", "\"1.3.0\"")
+    );
+
+    let path = dir.path().join("CMakeLists.txt");
+    Cmake::parse(None, path.to_str()).set_version("1.2.4").await;
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "project(foo VERSION 1.2.4 LANGUAGES CXX)\n"
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "cmake project version must be numeric")]
+async fn cmake_prerelease() {
+    let dir = super::copy_fixture("cmake/prerelease");
+    let path = dir.path().join("CMakeLists.txt");
+    Cmake::parse(None, path.to_str())
+        .set_version("1.3.0-rc.1")
+        .await;
+}
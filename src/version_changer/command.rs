@@ -1,9 +1,13 @@
 use crate::env::env_file;
 use crate::utils::MaybeStdin;
-use crate::version_changer::{parse_version_changers, VersionChangers};
+use crate::version_changer::cargo::Cargo;
+use crate::version_changer::{
+    changer_string, create_single_changer, parse_version_changers, VersionChangers,
+};
 use crate::CmdResult;
 use clap::Parser;
 use std::env;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 struct ChangerCommand {
@@ -50,6 +54,25 @@ pub enum VersionChangerCommand {
         #[arg(default_value_t = Default::default())]
         version: MaybeStdin<String>,
     },
+    /// Converts cargo packages to inherit the workspace version, or detaches them to explicit versions
+    CargoWorkspaceVersion {
+        /// names or glob patterns of the packages
+        #[arg(required = true)]
+        packages: Vec<String>,
+        /// converts to explicit versions instead
+        #[arg(long)]
+        detach: bool,
+        #[arg(long)]
+        manifest_path: Option<PathBuf>,
+    },
+    /// Prints the string representation of the version changer for RELEASE_CHANGER
+    ChangerString {
+        kind: String,
+        #[arg(short, long)]
+        info: Option<String>,
+        #[arg(short, long)]
+        path: Option<String>,
+    },
 }
 
 impl VersionChangerCommand {
@@ -68,6 +91,28 @@ impl VersionChangerCommand {
                     .await;
                 ok!()
             }
+            CargoWorkspaceVersion {
+                packages,
+                detach,
+                manifest_path,
+            } => {
+                let cargo = Cargo::new_for_packages(manifest_path, packages);
+                if detach {
+                    cargo.detach_workspace_version();
+                } else {
+                    cargo.inherit_workspace_version();
+                }
+                ok!()
+            }
+            ChangerString { kind, info, path } => {
+                // check if the changer is valid
+                create_single_changer(&kind, info.as_deref(), path.as_deref());
+                println!(
+                    "{}",
+                    changer_string(&kind, info.as_deref(), path.as_deref())
+                );
+                ok!()
+            }
         }
     }
 }
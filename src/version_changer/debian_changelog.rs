@@ -0,0 +1,387 @@
+use crate::version_changer::VersionChanger;
+use regex::Regex;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Adds a new entry to the top of `debian/changelog`.
+/// The package name, distribution, urgency and maintainer are taken from the current top entry if not specified.
+#[derive(Debug, Deserialize)]
+pub(crate) struct DebianChangelog {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    #[serde(default = "message_default", alias = "info")]
+    message: String,
+    distribution: Option<String>,
+    urgency: Option<String>,
+    /// `Name <email>`. defaults to `DEBFULLNAME` and `DEBEMAIL`, then the maintainer of the top entry
+    maintainer: Option<String>,
+    /// RFC 2822 date. defaults to `SOURCE_DATE_EPOCH` or the current time
+    date: Option<String>,
+    /// debian revision for the new entry. ignored for native packages
+    #[serde(default = "revision_default")]
+    revision: String,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("debian/changelog")
+}
+
+fn message_default() -> String {
+    "New upstream release.".to_owned()
+}
+
+fn revision_default() -> String {
+    "1".to_owned()
+}
+
+impl Display for DebianChangelog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "debian-changelog(at {})", self.path.display())
+    }
+}
+
+/// The top entry of the changelog
+struct Entry<'a> {
+    package: &'a str,
+    version: &'a str,
+    distribution: &'a str,
+    /// `urgency=medium` and other key-value pairs
+    options: &'a str,
+    maintainer: Option<&'a str>,
+}
+
+impl DebianChangelog {
+    async fn read(&self) -> String {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", self.path.display(), e))
+    }
+
+    fn top_entry<'a>(&self, source: &'a str) -> Entry<'a> {
+        let header_regex = Regex::new(r"^(\S+) \(([^()\s]+)\) ([^;]+); *(.*?)\s*$").unwrap();
+        let trailer_regex = Regex::new(r"^ -- (.+?)  (\S.*?)\s*$").unwrap();
+        let mut lines = source.lines().skip_while(|x| x.trim().is_empty());
+        let header = lines
+            .next()
+            .unwrap_or_else(|| panic!("no entry in {}", self.path.display()));
+        let captures = header_regex.captures(header).unwrap_or_else(|| {
+            panic!(
+                "invalid changelog entry in {}: {header}",
+                self.path.display()
+            )
+        });
+        let maintainer = lines
+            .take_while(|x| header_regex.captures(x).is_none())
+            .find_map(|x| trailer_regex.captures(x))
+            .map(|x| x.get(1).unwrap().as_str());
+        Entry {
+            package: captures.get(1).unwrap().as_str(),
+            version: captures.get(2).unwrap().as_str(),
+            distribution: captures.get(3).unwrap().as_str(),
+            options: captures.get(4).unwrap().as_str(),
+            maintainer,
+        }
+    }
+
+    fn maintainer(&self, entry: &Entry) -> String {
+        if let Some(maintainer) = &self.maintainer {
+            return maintainer.clone();
+        }
+        if let (Ok(name), Ok(email)) = (std::env::var("DEBFULLNAME"), std::env::var("DEBEMAIL")) {
+            return format!("{name} <{email}>");
+        }
+        entry
+            .maintainer
+            .unwrap_or_else(|| panic!("no maintainer in {}", self.path.display()))
+            .to_owned()
+    }
+
+    fn date(&self) -> String {
+        if let Some(date) = &self.date {
+            return date.clone();
+        }
+        let epoch = match std::env::var("SOURCE_DATE_EPOCH") {
+            Ok(epoch) => epoch
+                .parse()
+                .unwrap_or_else(|_| panic!("invalid SOURCE_DATE_EPOCH: {epoch}")),
+            Err(_) => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system time before unix epoch")
+                .as_secs(),
+        };
+        rfc2822_date(epoch)
+    }
+}
+
+impl VersionChanger for DebianChangelog {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            message: info.map(Into::into).unwrap_or_else(message_default),
+            distribution: None,
+            urgency: None,
+            maintainer: None,
+            date: None,
+            revision: revision_default(),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = self.read().await;
+        let version = self.top_entry(&source).version;
+        // remove epoch and debian revision
+        let upstream = version.split_once(':').map_or(version, |(_, x)| x);
+        let upstream = upstream.rsplit_once('-').map_or(upstream, |(x, _)| x);
+        from_tilde_version(upstream)
+    }
+
+    async fn set_version(&self, version: &str) {
+        let source = self.read().await;
+        let entry = self.top_entry(&source);
+
+        let epoch = entry.version.split_once(':').map(|(epoch, _)| epoch);
+        let native = !entry.version.contains('-');
+        let mut new_version = to_tilde_version(version);
+        if let Some(epoch) = epoch {
+            new_version = format!("{epoch}:{new_version}");
+        }
+        if !native {
+            new_version = format!("{new_version}-{}", self.revision);
+        }
+        if compare_versions(&new_version, entry.version) != Ordering::Greater {
+            panic!(
+                "{new_version} is not newer than {} in {}",
+                entry.version,
+                self.path.display()
+            );
+        }
+
+        let distribution = self.distribution.as_deref().unwrap_or(entry.distribution);
+        let options = match &self.urgency {
+            Some(urgency) => format!("urgency={urgency}"),
+            None => entry.options.to_owned(),
+        };
+
+        let mut new_entry = format!(
+            "{} ({new_version}) {distribution}; {options}\n\n",
+            entry.package
+        );
+        for (i, line) in self.message.lines().enumerate() {
+            let bullet = if i == 0 { "  * " } else { "    " };
+            new_entry.push_str(bullet);
+            new_entry.push_str(line.trim_end());
+            new_entry.push('\n');
+        }
+        new_entry.push_str(&format!(
+            "\n -- {}  {}\n\n",
+            self.maintainer(&entry),
+            self.date()
+        ));
+
+        tokio::fs::write(&self.path, new_entry + source.trim_start())
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+/// Compares debian versions `[epoch:]upstream[-revision]` like dpkg
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (u64, &str, &str) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => (epoch.parse().unwrap_or(0), rest),
+            None => (0, version),
+        };
+        let (upstream, revision) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        (epoch, upstream, revision)
+    }
+
+    let (a_epoch, a_upstream, a_revision) = split(a);
+    let (b_epoch, b_upstream, b_revision) = split(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| compare_part(a_upstream, b_upstream))
+        .then_with(|| compare_part(a_revision, b_revision))
+}
+
+/// `verrevcmp` of dpkg: non-digit parts are compared with `~` sorting before anything
+/// and letters sorting before other characters, and digit parts are compared numerically
+fn compare_part(a: &str, b: &str) -> Ordering {
+    fn order(c: Option<u8>) -> i32 {
+        match c {
+            None => 0,
+            Some(c) if c.is_ascii_digit() => 0,
+            Some(c) if c.is_ascii_alphabetic() => c as i32,
+            Some(b'~') => -1,
+            Some(c) => c as i32 + 256,
+        }
+    }
+
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    while !a.is_empty() || !b.is_empty() {
+        while a.first().is_some_and(|x| !x.is_ascii_digit())
+            || b.first().is_some_and(|x| !x.is_ascii_digit())
+        {
+            let ordering = order(a.first().copied()).cmp(&order(b.first().copied()));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            a = a.get(1..).unwrap_or_default();
+            b = b.get(1..).unwrap_or_default();
+        }
+        let digits = |x: &[u8]| x.iter().take_while(|x| x.is_ascii_digit()).count();
+        let (a_digits, b_digits) = (digits(a), digits(b));
+        let a_number = std::str::from_utf8(&a[..a_digits])
+            .unwrap()
+            .trim_start_matches('0');
+        let b_number = std::str::from_utf8(&b[..b_digits])
+            .unwrap()
+            .trim_start_matches('0');
+        let ordering = a_number
+            .len()
+            .cmp(&b_number.len())
+            .then_with(|| a_number.cmp(b_number));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        a = &a[a_digits..];
+        b = &b[b_digits..];
+    }
+    Ordering::Equal
+}
+
+/// Converts semver prerelease to tilde convention sorting before the release: `1.0.0-rc.1` to `1.0.0~rc.1`.
+/// The identifiers are kept as is so that [from_tilde_version] restores the exact version
+pub(super) fn to_tilde_version(version: &str) -> String {
+    let Some((release, rest)) = version.split_once('-') else {
+        return version.to_owned();
+    };
+    // `-` is the separator of revision in debian and not allowed in rpm
+    if rest.contains('-') {
+        panic!(
+            "prerelease or build metadata with `-` cannot be converted to tilde version: {version}"
+        );
+    }
+    format!("{release}~{rest}")
+}
+
+/// The reverse of [to_tilde_version]: `1.0.0~rc.1` to `1.0.0-rc.1`
+pub(super) fn from_tilde_version(version: &str) -> String {
+    version.replacen('~', "-", 1)
+}
+
+/// Formats the unix time like `Mon, 01 Jan 2024 00:00:00 +0000`
+fn rfc2822_date(epoch: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let days = epoch / 86400;
+    let seconds = epoch % 86400;
+
+    // civil_from_days by Howard Hinnant
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[(days % 7) as usize],
+        MONTHS[month as usize - 1],
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+    )
+}
+
+#[test]
+fn tilde_version() {
+    for (version, tilde) in [
+        ("1.2.0-rc.1", "1.2.0~rc.1"),
+        ("1.2.0-rc1", "1.2.0~rc1"),
+        ("1.2.0-beta.2.x", "1.2.0~beta.2.x"),
+        ("1.2.0-SNAPSHOT+git.1", "1.2.0~SNAPSHOT+git.1"),
+        ("1.2.0", "1.2.0"),
+        ("1.2.0+build", "1.2.0+build"),
+    ] {
+        assert_eq!(to_tilde_version(version), tilde);
+        assert_eq!(from_tilde_version(tilde), version);
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot be converted to tilde version")]
+fn tilde_version_hyphen() {
+    to_tilde_version("1.2.0-rc-1");
+}
+
+#[test]
+fn compare_versions_test() {
+    assert_eq!(compare_versions("1.2.0-1", "1.2.0-2"), Ordering::Less);
+    assert_eq!(compare_versions("1.2.0~rc.1-1", "1.2.0-1"), Ordering::Less);
+    assert_eq!(compare_versions("1.10.0-1", "1.9.0-1"), Ordering::Greater);
+    assert_eq!(compare_versions("1:0.1-1", "2.0-1"), Ordering::Greater);
+    assert_eq!(compare_versions("1.0a", "1.0+"), Ordering::Less);
+    assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Less);
+    assert_eq!(compare_versions("1.01", "1.1"), Ordering::Equal);
+}
+
+#[tokio::test]
+#[should_panic(expected = "1.2.0-1 is not newer than 1.2.0-2")]
+async fn lower_revision() {
+    let dir = super::copy_fixture("debian-changelog/revision");
+    let path = dir.path().join("changelog");
+    DebianChangelog::parse(None, path.to_str())
+        .set_version("1.2.0")
+        .await;
+}
+
+#[test]
+fn rfc2822_date_test() {
+    assert_eq!(rfc2822_date(0), "Thu, 01 Jan 1970 00:00:00 +0000");
+    assert_eq!(rfc2822_date(1709210096), "Thu, 29 Feb 2024 12:34:56 +0000");
+}
+
+#[tokio::test]
+async fn changelog() {
+    let dir = super::copy_fixture("debian-changelog");
+    let path = dir.path().join("changelog");
+    let source = std::fs::read_to_string(&path).unwrap();
+
+    let changer = DebianChangelog {
+        path: path.clone(),
+        message: "Release 1.2.0~rc.1.\nSee NEWS for details.".to_owned(),
+        distribution: None,
+        urgency: Some("low".to_owned()),
+        maintainer: None,
+        date: Some("Thu, 29 Feb 2024 12:34:56 +0000".to_owned()),
+        revision: revision_default(),
+    };
+    assert_eq!(changer.load_version().await, "1.1.0");
+    changer.set_version("1.2.0-rc.1").await;
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        format!(
+            "\
+example (1:1.2.0~rc.1-1) unstable; urgency=low
+
+  * Release 1.2.0~rc.1.
+    See NEWS for details.
+
+ -- Jane Doe <jane@example.com>  Thu, 29 Feb 2024 12:34:56 +0000
+
+{source}"
+        )
+    );
+    assert_eq!(changer.load_version().await, "1.2.0-rc.1");
+}
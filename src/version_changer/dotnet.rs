@@ -0,0 +1,290 @@
+use crate::utils::xml::{ElementId, XmlDocument};
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Dotnet {
+    /// path to .csproj, Directory.Build.props, or .nuspec file
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    /// if true, the version is written to `VersionPrefix` and `VersionSuffix` instead of `Version`.
+    /// `split-prerelease` as info in the changer string
+    #[serde(default)]
+    split_prerelease: bool,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("Directory.Build.props")
+}
+
+impl Display for Dotnet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dotnet(at {})", self.path.display())
+    }
+}
+
+/// properties derived from the version. they're updated only if exists
+const DERIVED_PROPERTIES: &[&str] = &["AssemblyVersion", "FileVersion"];
+
+impl VersionChanger for Dotnet {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let split_prerelease = match info {
+            None => false,
+            Some("split-prerelease") => true,
+            Some(info) => panic!("invalid dotnet version changer: unknown option {info}"),
+        };
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            split_prerelease,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = tokio::fs::read_to_string(&self.path)
+            .await
+            .expect("reading project file");
+        let document = XmlDocument::parse(&source).expect("parsing project file");
+
+        if is_nuspec(&document) {
+            return text(&document, nuspec_version(&document));
+        }
+
+        if let Some(version) = find_property(&document, "Version") {
+            text(&document, version)
+        } else if let Some(prefix) = find_property(&document, "VersionPrefix") {
+            let prefix = text(&document, prefix);
+            match find_property(&document, "VersionSuffix").map(|x| text(&document, x)) {
+                Some(suffix) if !suffix.is_empty() => format!("{prefix}-{suffix}"),
+                _ => prefix,
+            }
+        } else {
+            panic!("no <Version> nor <VersionPrefix> in project file");
+        }
+    }
+
+    async fn set_version(&self, version: &str) {
+        let source = tokio::fs::read_to_string(&self.path)
+            .await
+            .expect("reading project file");
+        let mut document = XmlDocument::parse(&source).expect("parsing project file");
+
+        if is_nuspec(&document) {
+            document.set_text(nuspec_version(&document), version);
+        } else {
+            self.set_project_version(&mut document, version);
+        }
+
+        tokio::fs::write(&self.path, document.to_string())
+            .await
+            .expect("writing project file");
+    }
+}
+
+impl Dotnet {
+    fn set_project_version(&self, document: &mut XmlDocument, version: &str) {
+        let version_element = find_property(document, "Version");
+        let prefix_element = find_property(document, "VersionPrefix");
+
+        if self.split_prerelease || (version_element.is_none() && prefix_element.is_some()) {
+            // `+build` would end up in VersionPrefix or VersionSuffix and cannot be read back
+            if version.contains('+') {
+                panic!("build metadata cannot be written to <VersionPrefix> and <VersionSuffix>: {version}");
+            }
+            let (prefix, suffix) = match version.split_once('-') {
+                Some((prefix, suffix)) => (prefix, Some(suffix)),
+                None => (version, None),
+            };
+
+            // <Version> overrides <VersionPrefix> and <VersionSuffix> so it's replaced with them
+            let anchor = match prefix_element {
+                Some(prefix_element) => {
+                    check_literal(document, prefix_element);
+                    document.set_text(prefix_element, prefix);
+                    if let Some(version_element) = version_element {
+                        document.remove(version_element);
+                    }
+                    prefix_element
+                }
+                None => {
+                    let version_element =
+                        version_element.expect("no <Version> nor <VersionPrefix> in project file");
+                    check_literal(document, version_element);
+                    document.insert_after(version_element, "VersionPrefix", prefix);
+                    document.remove(version_element);
+                    version_element
+                }
+            };
+            match (find_property(document, "VersionSuffix"), suffix) {
+                (Some(element), Some(suffix)) => document.set_text(element, suffix),
+                (Some(element), None) => document.remove(element),
+                (None, Some(suffix)) => document.insert_after(anchor, "VersionSuffix", suffix),
+                (None, None) => {}
+            }
+        } else {
+            let version_element = version_element.expect("no <Version> in project file");
+            check_literal(document, version_element);
+            document.set_text(version_element, version);
+        }
+
+        for name in DERIVED_PROPERTIES {
+            if let Some(element) = find_property(document, name) {
+                if text(document, element).contains("$(") {
+                    // computed from other properties
+                    continue;
+                }
+                document.set_text(element, &assembly_version(version));
+            }
+        }
+    }
+}
+
+fn is_nuspec(document: &XmlDocument) -> bool {
+    document.name(document.find(&[]).unwrap()) == "package"
+}
+
+fn nuspec_version(document: &XmlDocument) -> ElementId {
+    document
+        .find(&["metadata", "version"])
+        .expect("no <metadata><version> in nuspec")
+}
+
+fn find_property(document: &XmlDocument, name: &str) -> Option<ElementId> {
+    let project = document.find(&[]).unwrap();
+    document
+        .children(project)
+        .filter(|&group| document.name(group) == "PropertyGroup")
+        .find_map(|group| document.child(group, name))
+}
+
+fn text(document: &XmlDocument, element: ElementId) -> String {
+    document
+        .text(element)
+        .unwrap_or_else(|| panic!("<{}> is not a text", document.name(element)))
+        .trim()
+        .to_owned()
+}
+
+fn check_literal(document: &XmlDocument, element: ElementId) {
+    if text(document, element).contains("$(") {
+        panic!(
+            "<{}> is computed from other properties",
+            document.name(element)
+        );
+    }
+}
+
+/// Creates four-part assembly version from the version. `1.2.3-beta.1` => `1.2.3.0`
+fn assembly_version(version: &str) -> String {
+    let release = version.split(['-', '+']).next().unwrap();
+    let mut parts = release
+        .split('.')
+        .map(|part| {
+            // 65535 is reserved
+            part.parse::<u16>()
+                .ok()
+                .filter(|&x| x <= 65534)
+                .unwrap_or_else(|| panic!("version {version} cannot be an assembly version"))
+        })
+        .collect::<Vec<_>>();
+    if parts.len() > 4 {
+        panic!("version {version} cannot be an assembly version");
+    }
+    parts.resize(4, 0);
+    parts
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Sets the version of the project file in the copied `dotnet` fixture and returns the updated file
+#[cfg(test)]
+async fn test_project(dir: &tempfile::TempDir, changer: Dotnet, version: &str) -> String {
+    let changer = Dotnet {
+        path: dir.path().join(&changer.path),
+        ..changer
+    };
+    changer.set_version(version).await;
+    assert_eq!(changer.load_version().await, version);
+    std::fs::read_to_string(&changer.path).unwrap()
+}
+
+#[tokio::test]
+async fn project_version() {
+    let dir = super::copy_fixture("dotnet");
+    let changer = Dotnet::parse(None, Some("Example.csproj"));
+    assert_eq!(
+        test_project(&dir, changer, "1.2.0-beta.1").await,
+        r#"<Project Sdk="Microsoft.NET.Sdk">
+
+  <PropertyGroup>
+    <TargetFramework>net8.0</TargetFramework>
+    <!-- the version of the package -->
+    <Version>1.2.0-beta.1</Version>
+    <AssemblyVersion>1.2.0.0</AssemblyVersion>
+    <FileVersion>$(AssemblyVersion)</FileVersion>
+  </PropertyGroup>
+
+</Project>
+"#
+    );
+}
+
+#[tokio::test]
+async fn split_prerelease() {
+    let dir = super::copy_fixture("dotnet");
+    let changer = || Dotnet::parse(None, Some("prefix/Directory.Build.props"));
+    assert_eq!(
+        test_project(&dir, changer(), "1.1.0-rc.1").await,
+        "<Project>\r\n  <PropertyGroup>\r\n    <VersionPrefix>1.1.0</VersionPrefix>\r\n    <VersionSuffix>rc.1</VersionSuffix>\r\n  </PropertyGroup>\r\n</Project>\r\n"
+    );
+    assert_eq!(
+        test_project(&dir, changer(), "1.1.0").await,
+        "<Project>\r\n  <PropertyGroup>\r\n    <VersionPrefix>1.1.0</VersionPrefix>\r\n  </PropertyGroup>\r\n</Project>\r\n"
+    );
+}
+
+#[tokio::test]
+async fn split_existing_version() {
+    let dir = super::copy_fixture("dotnet");
+    let changer = Dotnet::parse(Some("split-prerelease"), None);
+    assert_eq!(
+        test_project(&dir, changer, "1.1.0-rc.1").await,
+        "<Project>\n  <PropertyGroup>\n    <VersionPrefix>1.1.0</VersionPrefix>\n    <VersionSuffix>rc.1</VersionSuffix>\n    <Authors>me</Authors>\n  </PropertyGroup>\n</Project>\n"
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "build metadata cannot be written")]
+async fn split_build_metadata() {
+    let dir = super::copy_fixture("dotnet");
+    let changer = Dotnet::parse(None, Some("prefix/Directory.Build.props"));
+    test_project(&dir, changer, "1.2.3-rc.1+abc").await;
+}
+
+#[tokio::test]
+async fn nuspec() {
+    let dir = super::copy_fixture("dotnet");
+    let changer = Dotnet::parse(None, Some("Example.nuspec"));
+    let source = std::fs::read_to_string(dir.path().join("Example.nuspec")).unwrap();
+    assert_eq!(
+        test_project(&dir, changer, "2.0.0").await,
+        source.replace("1.0.0", "2.0.0")
+    );
+}
+
+#[test]
+fn assembly_version_test() {
+    assert_eq!(assembly_version("1"), "1.0.0.0");
+    assert_eq!(assembly_version("1.2.3-beta.1+build"), "1.2.3.0");
+    assert_eq!(assembly_version("1.2.3.4"), "1.2.3.4");
+    assert_eq!(assembly_version("65534.0"), "65534.0.0.0");
+}
+
+#[test]
+#[should_panic(expected = "cannot be an assembly version")]
+fn assembly_version_reserved() {
+    assembly_version("1.65535.0");
+}
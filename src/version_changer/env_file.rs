@@ -0,0 +1,110 @@
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Writes the version to the dotenv file as `KEY=version`. This changer is write-only.
+#[derive(Debug, Deserialize)]
+pub(crate) struct EnvFile {
+    #[serde(alias = "info")]
+    path: PathBuf,
+    #[serde(default = "key_default")]
+    key: String,
+}
+
+fn key_default() -> String {
+    "VERSION".to_owned()
+}
+
+impl Display for EnvFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "env-file(at {} key {})", self.path.display(), self.key)
+    }
+}
+
+impl VersionChanger for EnvFile {
+    const READABLE: bool = false;
+
+    /// `env-file:path` or `env-file:KEY@path`
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let (key, path) = match (info, path) {
+            (key, Some(path)) => (key.map(Into::into).unwrap_or_else(key_default), path),
+            (Some(path), None) => (key_default(), path),
+            (None, None) => panic!("env-file needs path"),
+        };
+        Self {
+            path: path.into(),
+            key,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        unreachable!("env-file is write-only")
+    }
+
+    async fn set_version(&self, version: &str) {
+        if version.contains(['\n', '\r']) {
+            panic!("version cannot contain newline");
+        }
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => panic!("reading {}: {}", self.path.display(), e),
+        };
+        tokio::fs::write(&self.path, set_variable(&content, &self.key, version))
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+/// Replaces the existing assignment of the key, or appends one
+fn set_variable(content: &str, key: &str, value: &str) -> String {
+    let is_assignment = |line: &str| {
+        let line = line.trim_start();
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        line.strip_prefix(key)
+            .is_some_and(|x| x.trim_start().starts_with('='))
+    };
+
+    let mut result = String::with_capacity(content.len() + key.len() + value.len() + 2);
+    let mut found = false;
+    for line in content.split_inclusive('\n') {
+        if !found && is_assignment(line) {
+            // keep the whitespace around `=`
+            let equals = line.find('=').unwrap() + 1;
+            let space = line[equals..].len() - line[equals..].trim_start_matches([' ', '\t']).len();
+            let prefix = &line[..equals + space];
+            let newline = &line[line.trim_end_matches(['\r', '\n']).len()..];
+            result.push_str(prefix);
+            result.push_str(value);
+            result.push_str(newline);
+            found = true;
+        } else {
+            result.push_str(line);
+        }
+    }
+    if !found {
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push_str(&format!("{key}={value}\n"));
+    }
+    result
+}
+
+#[test]
+fn set_variable_test() {
+    assert_eq!(set_variable("", "VERSION", "1.0.0"), "VERSION=1.0.0\n");
+    assert_eq!(
+        set_variable(
+            "NAME=app\nexport VERSION = 0.1.0\r\nOTHER=1",
+            "VERSION",
+            "1.0.0"
+        ),
+        "NAME=app\nexport VERSION = 1.0.0\r\nOTHER=1"
+    );
+    assert_eq!(
+        set_variable("VERSION_CODE=1", "VERSION", "1.0.0"),
+        "VERSION_CODE=1\nVERSION=1.0.0\n"
+    );
+}
@@ -0,0 +1,106 @@
+//! The version changer delegating to an external executable.
+//!
+//! The executable is invoked with the following arguments, and `path` of the changer is appended if specified.
+//!
+//! - `get`: prints the version to stdout. Leading and trailing whitespaces are ignored.
+//! - `set <version>`: sets the version.
+//!
+//! Exiting with non-zero status is an error. stderr of the executable is passed through.
+
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::process::{Output, Stdio};
+use tokio::process::Command;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Exec {
+    #[serde(alias = "info")]
+    command: String,
+    path: Option<String>,
+}
+
+impl Display for Exec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "exec({} at {})", self.command, path),
+            None => write!(f, "exec({})", self.command),
+        }
+    }
+}
+
+impl Exec {
+    async fn run(&self, args: &[&str]) -> Output {
+        let mut command = Command::new(&self.command);
+        command
+            .args(args)
+            .args(&self.path)
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit());
+        let output = command
+            .output()
+            .await
+            .unwrap_or_else(|e| panic!("running {}: {}", self.command, e));
+        if !output.status.success() {
+            panic!("{} {} failed with {}", self.command, args[0], output.status);
+        }
+        output
+    }
+}
+
+impl VersionChanger for Exec {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        Self {
+            command: info.expect("exec needs executable").to_owned(),
+            path: path.map(Into::into),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let output = self.run(&["get"]).await;
+        let version = String::from_utf8(output.stdout)
+            .unwrap_or_else(|_| panic!("{} get printed non utf-8 version", self.command));
+        let version = version.trim();
+        if version.is_empty() || version.contains('\n') {
+            panic!(
+                "{} get should print single line version but printed {:?}",
+                self.command, version
+            );
+        }
+        version.to_owned()
+    }
+
+    async fn set_version(&self, version: &str) {
+        self.run(&["set", version]).await;
+    }
+}
+
+// the plugins are not written in tests since executing a file written just before
+// fails with ETXTBSY if another test forks while the file is open
+#[cfg(unix)]
+#[tokio::test]
+async fn plugin() {
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("seed.txt");
+    std::fs::write(&data, "1.0.0\n").unwrap();
+
+    let changer = Exec::parse(
+        Some("__tests__resources/version-changer/exec/plugin.sh"),
+        data.to_str(),
+    );
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0").await;
+    assert_eq!(std::fs::read_to_string(&data).unwrap(), "1.1.0\n");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+#[should_panic(expected = "set failed with exit status: 3")]
+async fn plugin_failure() {
+    Exec::parse(
+        Some("__tests__resources/version-changer/exec/failing-plugin.sh"),
+        None,
+    )
+    .set_version("1.0.0")
+    .await;
+}
@@ -0,0 +1,95 @@
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use tokio::process::Command;
+
+/// Reads the version from the nearest tag reachable from `HEAD` found by `git describe`,
+/// which may not be the highest version if another branch has newer tags. This changer is read-only.
+#[derive(Debug, Deserialize)]
+pub(crate) struct GitTag {
+    /// the directory of the repository
+    path: Option<PathBuf>,
+    /// the prefix of tags like `v` for `v1.0.0`
+    #[serde(default = "prefix_default", alias = "info")]
+    prefix: String,
+}
+
+fn prefix_default() -> String {
+    "v".to_owned()
+}
+
+impl Display for GitTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "git-tag(at {} prefix {})", path.display(), self.prefix),
+            None => write!(f, "git-tag(prefix {})", self.prefix),
+        }
+    }
+}
+
+impl VersionChanger for GitTag {
+    const WRITABLE: bool = false;
+
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        Self {
+            path: path.map(Into::into),
+            prefix: info.map(Into::into).unwrap_or_else(prefix_default),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let mut command = Command::new("git");
+        command
+            .args(["describe", "--tags", "--abbrev=0", "--match"])
+            .arg(format!("{}*", self.prefix));
+        if let Some(path) = &self.path {
+            command.current_dir(path);
+        }
+        let output = command.output().await.expect("running git");
+        if !output.status.success() {
+            panic!(
+                "no tag with prefix {:?} found: {}",
+                self.prefix,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let tag = String::from_utf8(output.stdout).expect("tag is not utf-8");
+        let tag = tag.trim();
+        tag.strip_prefix(&self.prefix)
+            .unwrap_or_else(|| panic!("tag {tag} doesn't start with {}", self.prefix))
+            .to_owned()
+    }
+
+    async fn set_version(&self, _: &str) {
+        unreachable!("git-tag is read-only")
+    }
+}
+
+#[tokio::test]
+async fn latest_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?}");
+    };
+    git(&["init", "-q"]);
+    git(&["commit", "-q", "--allow-empty", "-m", "first"]);
+    git(&["tag", "v1.0.0"]);
+    git(&["commit", "-q", "--allow-empty", "-m", "second"]);
+    git(&["tag", "v1.1.0"]);
+    git(&["tag", "other-2.0.0"]);
+
+    let path = dir.path().to_str();
+    assert_eq!(GitTag::parse(None, path).load_version().await, "1.1.0");
+    assert_eq!(
+        GitTag::parse(Some("other-"), path).load_version().await,
+        "2.0.0"
+    );
+}
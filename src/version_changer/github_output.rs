@@ -0,0 +1,61 @@
+use crate::github_actions_utilities::set_output;
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+
+/// Sets the version to the output of the GitHub Actions step. This changer is write-only.
+#[derive(Debug, Deserialize)]
+pub(crate) struct GithubOutput {
+    #[serde(default = "name_default", alias = "info")]
+    name: String,
+}
+
+fn name_default() -> String {
+    "version".to_owned()
+}
+
+impl Display for GithubOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "github-output({})", self.name)
+    }
+}
+
+impl VersionChanger for GithubOutput {
+    const READABLE: bool = false;
+
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        if path.is_some() {
+            panic!("invalid github-output version changer: path is not supported");
+        }
+        Self {
+            name: info.map(Into::into).unwrap_or_else(name_default),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        unreachable!("github-output is write-only")
+    }
+
+    async fn set_version(&self, version: &str) {
+        set_output(self.name.clone(), version).expect("setting github output");
+    }
+}
+
+#[tokio::test]
+async fn append_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("output");
+    std::fs::write(&path, "other=1\n").unwrap();
+    // no other test uses GITHUB_OUTPUT
+    std::env::set_var("GITHUB_OUTPUT", &path);
+    GithubOutput::parse(Some("next"), None)
+        .set_version("1.2.0")
+        .await;
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let appended = content.strip_prefix("other=1\n").unwrap();
+    let (key, rest) = appended.split_once("<<").unwrap();
+    let (delimiter, rest) = rest.split_once('\n').unwrap();
+    assert_eq!(key, "next");
+    assert_eq!(rest, format!("1.2.0\n{delimiter}\n"));
+}
@@ -0,0 +1,375 @@
+use crate::utils::gradle::{escape_groovy_string, unescape_groovy_string};
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GradleBuildScript {
+    /// path to build script. if not specified, build.gradle.kts or build.gradle is used
+    path: Option<PathBuf>,
+}
+
+impl Display for GradleBuildScript {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "gradle-build-script(at {})", path.display()),
+            None => write!(f, "gradle-build-script"),
+        }
+    }
+}
+
+impl GradleBuildScript {
+    async fn path(&self) -> PathBuf {
+        if let Some(path) = &self.path {
+            return path.clone();
+        }
+        let kts = PathBuf::from("build.gradle.kts");
+        if tokio::fs::try_exists(&kts)
+            .await
+            .expect("checking build.gradle.kts")
+        {
+            kts
+        } else {
+            PathBuf::from("build.gradle")
+        }
+    }
+}
+
+impl VersionChanger for GradleBuildScript {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        if info.is_some() {
+            panic!("invalid gradle-build-script version changer");
+        }
+        Self {
+            path: path.map(Into::into),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let path = self.path().await;
+        let script = tokio::fs::read_to_string(&path)
+            .await
+            .expect("reading build script");
+        find_version(&script).value
+    }
+
+    async fn set_version(&self, version: &str) {
+        let path = self.path().await;
+        let mut script = tokio::fs::read_to_string(&path)
+            .await
+            .expect("reading build script");
+        let literal = find_version(&script).literal;
+        let quote = &script[literal.start..literal.start + 1];
+        let replacement = format!("{quote}{}{quote}", escape_groovy_string(version));
+        script.replace_range(literal, &replacement);
+        tokio::fs::write(&path, script)
+            .await
+            .expect("writing build script");
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(super) struct VersionLiteral {
+    /// range of string literal including quotes
+    pub literal: Range<usize>,
+    pub value: String,
+}
+
+fn find_version(script: &str) -> VersionLiteral {
+    let mut statements = version_statements(script).into_iter();
+    let Some(statement) = statements.next() else {
+        panic!("no version declaration found in build script");
+    };
+    if statements.next().is_some() {
+        panic!("multiple version declarations found in build script");
+    }
+
+    parse_declaration(script, statement.clone()).unwrap_or_else(|| {
+        panic!(
+            "version is not a string literal in build script: {}",
+            script[statement].trim()
+        )
+    })
+}
+
+/// Returns the range of statements assigning version of project.
+///
+/// Statements in the top level and in `allprojects` block are recognized.
+fn version_statements(script: &str) -> Vec<Range<usize>> {
+    block_statements(script)
+        .into_iter()
+        .filter(|(blocks, _)| blocks.iter().all(|x| x == "allprojects"))
+        .map(|(_, statement)| statement)
+        .filter(|statement| is_version_statement(&script[statement.clone()]))
+        .collect()
+}
+
+/// Returns the range of all statements with the headers of the blocks containing it like `["android", "defaultConfig"]`.
+pub(super) fn block_statements(script: &str) -> Vec<(Vec<String>, Range<usize>)> {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut blocks = Vec::<String>::new();
+    let mut statement_start = 0;
+    let mut code_end = 0;
+    let mut pos = 0;
+
+    let mut end_statement = |blocks: &[String], start: usize, end: usize| {
+        if !script[start..end].trim().is_empty() {
+            statements.push((blocks.to_vec(), start..end));
+        }
+    };
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\n' | b';' => {
+                end_statement(&blocks, statement_start, code_end);
+                pos += 1;
+                statement_start = pos;
+                code_end = pos;
+                continue;
+            }
+            b'{' => {
+                blocks.push(script[statement_start..pos].trim().to_owned());
+                pos += 1;
+                statement_start = pos;
+                code_end = pos;
+                continue;
+            }
+            b'}' => {
+                end_statement(&blocks, statement_start, code_end);
+                blocks.pop();
+                pos += 1;
+                statement_start = pos;
+                code_end = pos;
+                continue;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                pos += script[pos..].find('\n').unwrap_or(script.len() - pos);
+                continue;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos += script[pos..]
+                    .find("*/")
+                    .map(|x| x + 2)
+                    .unwrap_or(script.len() - pos);
+                continue;
+            }
+            b'"' | b'\'' => pos = skip_string(script, pos),
+            c if c.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            _ => pos += 1,
+        }
+        code_end = pos;
+    }
+    end_statement(&blocks, statement_start, code_end);
+
+    statements
+}
+
+fn is_version_statement(statement: &str) -> bool {
+    let statement = statement.trim_start();
+    let statement = statement.strip_prefix("project.").unwrap_or(statement);
+    match statement.strip_prefix("version") {
+        Some(rest) => !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.'),
+        None => false,
+    }
+}
+
+/// Returns the position after the string literal starting at `start`
+fn skip_string(script: &str, start: usize) -> usize {
+    let bytes = script.as_bytes();
+    let quote = bytes[start];
+    let triple = bytes.get(start..start + 3) == Some(&[quote; 3]);
+    let mut pos = if triple { start + 3 } else { start + 1 };
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'$' if quote == b'"' && bytes.get(pos + 1) == Some(&b'{') => {
+                // skip interpolation
+                let mut depth = 0;
+                while pos < bytes.len() {
+                    match bytes[pos] {
+                        b'{' => depth += 1,
+                        b'}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    pos += 1;
+                }
+                pos += 1;
+            }
+            c if c == quote && !triple => return pos + 1,
+            c if c == quote && bytes.get(pos..pos + 3) == Some(&[quote; 3]) => return pos + 3,
+            b'\n' if !triple => return pos,
+            _ => pos += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Parses `version = "x"`, `version "x"`, `version("x")` or `project.version = 'x'`
+fn parse_declaration(script: &str, statement: Range<usize>) -> Option<VersionLiteral> {
+    let text = &script[statement.clone()];
+    let rest = text.trim_start();
+    let prefix = if rest.starts_with("project.") { 8 } else { 0 };
+    let start = statement.start + (text.len() - rest.len()) + prefix;
+    parse_string_assignment(script, start..statement.end, "version")
+}
+
+/// Parses `name = "x"`, `name "x"` or `name("x")` with string literal
+pub(super) fn parse_string_assignment(
+    script: &str,
+    statement: Range<usize>,
+    name: &str,
+) -> Option<VersionLiteral> {
+    let value = assignment_value(script, statement, name)?;
+    let literal = &script[value.clone()];
+    let quote = literal.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let end = skip_string(script, value.start);
+    if end != value.end
+        || literal.len() < 2
+        || literal.starts_with(&quote.to_string().repeat(3))
+        || !literal.ends_with(quote)
+    {
+        return None;
+    }
+
+    let value = unescape_groovy_string(&literal[1..literal.len() - 1], quote == '"')?;
+    Some(VersionLiteral {
+        literal: start_of(script, literal)..end,
+        value,
+    })
+}
+
+/// Parses `name = value`, `name value` or `name(value)` and returns the range of value expression
+pub(super) fn assignment_value(
+    script: &str,
+    statement: Range<usize>,
+    name: &str,
+) -> Option<Range<usize>> {
+    let text = &script[statement.clone()];
+    let rest = text.trim_start().strip_prefix(name)?;
+
+    let rest = if let Some(rest) = rest.trim_start().strip_prefix('=') {
+        rest.trim()
+    } else if let Some(rest) = rest.trim_start().strip_prefix('(') {
+        rest.trim().strip_suffix(')')?.trim_end()
+    } else if rest.starts_with(char::is_whitespace) {
+        rest.trim()
+    } else {
+        return None;
+    };
+    if rest.is_empty() {
+        return None;
+    }
+    let start = start_of(script, rest);
+    Some(start..start + rest.len())
+}
+
+/// Returns the offset of the substring in the script
+fn start_of(script: &str, substring: &str) -> usize {
+    substring.as_ptr() as usize - script.as_ptr() as usize
+}
+
+#[cfg(test)]
+fn literal(script: &str, value: &str) -> VersionLiteral {
+    let start = script.find(value).unwrap() - 1;
+    VersionLiteral {
+        literal: start..start + value.len() + 2,
+        value: value.to_owned(),
+    }
+}
+
+#[test]
+fn find_version_groovy() {
+    let script = r#"plugins {
+    id 'java'
+    id 'org.jetbrains.kotlin.jvm' version '1.9.0'
+}
+
+group = 'com.anatawa12'
+version = '1.0.0' // the version
+
+dependencies {
+    implementation 'org.example:example:2.0.0'
+}
+
+android {
+    versionCode = 10
+}
+versionCode = 10
+
+publishing.publications.create("maven", MavenPublication) {
+    version = "3.0.0"
+}
+"#;
+    assert_eq!(find_version(script), literal(script, "1.0.0"));
+
+    let script = "version \"1.0.0\"\n";
+    assert_eq!(find_version(script), literal(script, "1.0.0"));
+
+    let script = "allprojects {\n  project.version = '1.0-SNAPSHOT';\n}\n";
+    assert_eq!(find_version(script), literal(script, "1.0-SNAPSHOT"));
+}
+
+#[test]
+fn find_version_kotlin() {
+    let script = r#"plugins {
+    kotlin("jvm") version "1.9.0"
+}
+
+group = "com.anatawa12"
+version = "1.0.0"
+
+tasks.jar {
+    manifest.attributes("Implementation-Version" to "${project.version}")
+}
+"#;
+    assert_eq!(find_version(script), literal(script, "1.0.0"));
+
+    let script = "/* version = \"0.0.0\" */\nversion(\"1.0.0\")\n";
+    assert_eq!(find_version(script), literal(script, "1.0.0"));
+}
+
+#[test]
+fn computed_version() {
+    for script in [
+        "version = \"1.0.${buildNumber}\"\n",
+        "version = rootProject.version\n",
+        "version = file('VERSION').text.trim()\n",
+        "version = '1.0' + suffix\n",
+    ] {
+        let declaration = version_statements(script)
+            .into_iter()
+            .map(|statement| parse_declaration(script, statement))
+            .collect::<Vec<_>>();
+        assert_eq!(declaration, vec![None], "{script}");
+    }
+}
+
+#[tokio::test]
+async fn set_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("build.gradle.kts");
+    std::fs::write(
+        &path,
+        "group = \"com.anatawa12\"\nversion = \"1.0.0\" // version\n",
+    )
+    .unwrap();
+
+    let changer = GradleBuildScript::parse(None, Some(path.to_str().unwrap()));
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0-SNAPSHOT").await;
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "group = \"com.anatawa12\"\nversion = \"1.1.0-SNAPSHOT\" // version\n"
+    );
+}
@@ -0,0 +1,152 @@
+use crate::utils;
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use toml_edit::{DocumentMut, Value};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GradleVersionCatalog {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    /// the key in `[versions]` table
+    #[serde(alias = "info")]
+    key: String,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("gradle/libs.versions.toml")
+}
+
+/// keys of rich version declaration which can hold the version
+const RICH_VERSION_KEYS: &[&str] = &["strictly", "require", "prefer"];
+
+impl Display for GradleVersionCatalog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "gradle-version-catalog(at {} key {})",
+            self.path.display(),
+            self.key
+        )
+    }
+}
+
+impl GradleVersionCatalog {
+    async fn read(&self) -> DocumentMut {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .expect("reading version catalog")
+            .parse()
+            .expect("parsing version catalog")
+    }
+
+    /// Returns the values holding the version.
+    /// For rich versions, all of `strictly`, `require`, and `prefer` are returned.
+    fn version_values<'a>(&self, document: &'a mut DocumentMut) -> Vec<&'a mut Value> {
+        let item = document
+            .get_mut("versions")
+            .and_then(|versions| versions.get_mut(&self.key))
+            .unwrap_or_else(|| panic!("no {} in [versions] of version catalog", self.key));
+
+        if item.is_str() {
+            vec![item.as_value_mut().unwrap()]
+        } else if let Some(table) = item.as_table_like_mut() {
+            let values = table
+                .iter_mut()
+                .filter(|(key, _)| RICH_VERSION_KEYS.contains(&key.get()))
+                .filter_map(|(_, value)| value.as_value_mut())
+                .collect::<Vec<_>>();
+            if values.is_empty() {
+                panic!("no strictly, require, nor prefer in version {}", self.key);
+            }
+            values
+        } else {
+            panic!(
+                "version {} in version catalog is not a string nor a table",
+                self.key
+            );
+        }
+    }
+}
+
+impl VersionChanger for GradleVersionCatalog {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            key: info
+                .expect("gradle-version-catalog needs key of version")
+                .to_owned(),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let mut document = self.read().await;
+        let mut version = None::<String>;
+        for value in self.version_values(&mut document) {
+            let value = value
+                .as_str()
+                .unwrap_or_else(|| panic!("version {} is not a string", self.key));
+            match &version {
+                None => version = Some(value.to_owned()),
+                Some(version) if version == value => {}
+                Some(version) => {
+                    panic!("version mismatch in {}: {} != {}", self.key, version, value)
+                }
+            }
+        }
+        version.unwrap()
+    }
+
+    async fn set_version(&self, version: &str) {
+        let mut document = self.read().await;
+        for value in self.version_values(&mut document) {
+            utils::toml::set_string(value, version);
+        }
+        tokio::fs::write(&self.path, document.to_string())
+            .await
+            .expect("writing version catalog");
+    }
+}
+
+#[tokio::test]
+async fn version_catalog() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("libs.versions.toml");
+    std::fs::write(
+        &path,
+        r#"[versions]
+# our libraries
+my-lib = "1.0.0" # the version
+my-strict = { strictly = "1.0.0" }
+my-rich = { require = '1.0.0', prefer = '1.0.0', reject = ["0.9.0"] }
+kotlin = "1.9.0"
+
+[libraries]
+my-lib = { module = "com.anatawa12:my-lib", version.ref = "my-lib" }
+"#,
+    )
+    .unwrap();
+    let path = path.to_str().unwrap();
+
+    for key in ["my-lib", "my-strict", "my-rich"] {
+        let changer = GradleVersionCatalog::parse(Some(key), Some(path));
+        assert_eq!(changer.load_version().await, "1.0.0");
+        changer.set_version("1.1.0").await;
+        assert_eq!(changer.load_version().await, "1.1.0");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        r#"[versions]
+# our libraries
+my-lib = "1.1.0" # the version
+my-strict = { strictly = "1.1.0" }
+my-rich = { require = '1.1.0', prefer = '1.1.0', reject = ["0.9.0"] }
+kotlin = "1.9.0"
+
+[libraries]
+my-lib = { module = "com.anatawa12:my-lib", version.ref = "my-lib" }
+"#
+    );
+}
@@ -0,0 +1,224 @@
+use crate::utils::yaml::{YamlDocument, YamlNode};
+use crate::version_changer::VersionChanger;
+use log::debug;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct HelmChart {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    /// the field holding the version
+    #[serde(default, alias = "info")]
+    field: ChartField,
+    /// if true, exact versions of `file://` dependencies are also updated with the chart version.
+    /// the referenced charts are not bumped, so use this with changers for them.
+    /// `local-dependencies` option in the changer string
+    #[serde(default)]
+    update_local_dependencies: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy)]
+enum ChartField {
+    #[default]
+    #[serde(rename = "version")]
+    Version,
+    #[serde(rename = "appVersion")]
+    AppVersion,
+    #[serde(rename = "both")]
+    Both,
+}
+
+impl ChartField {
+    fn keys(self) -> &'static [&'static str] {
+        match self {
+            ChartField::Version => &["version"],
+            ChartField::AppVersion => &["appVersion"],
+            ChartField::Both => &["version", "appVersion"],
+        }
+    }
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("Chart.yaml")
+}
+
+impl Display for HelmChart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "helm-chart(at {}", self.path.display())?;
+        for key in self.field.keys() {
+            write!(f, " {key}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl HelmChart {
+    async fn read(&self) -> String {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .expect("reading Chart.yaml")
+    }
+
+    fn version_scalars(&self, document: &YamlDocument) -> Vec<Range<usize>> {
+        self.field
+            .keys()
+            .iter()
+            .map(|key| {
+                document
+                    .root()
+                    .get(key)
+                    .unwrap_or_else(|| panic!("no {key} in Chart.yaml"))
+                    .as_scalar()
+                    .unwrap_or_else(|| panic!("{key} in Chart.yaml is not a scalar"))
+            })
+            .collect()
+    }
+}
+
+/// Returns the version of dependencies with `file://` repository
+fn local_dependency_versions(document: &YamlDocument) -> Vec<Range<usize>> {
+    let dependencies = document.root().get("dependencies");
+    dependencies
+        .map(YamlNode::items)
+        .unwrap_or_default()
+        .iter()
+        .filter(|dependency| {
+            dependency
+                .get("repository")
+                .and_then(YamlNode::as_scalar)
+                .is_some_and(|x| document.scalar(x).starts_with("file://"))
+        })
+        .filter_map(|dependency| dependency.get("version")?.as_scalar())
+        .filter(|version| {
+            // version constraints like `~1.0.0` or `>=1.0.0` are kept as is
+            let version = document.scalar(version.clone());
+            let exact = !version.contains(['^', '~', '<', '>', '=', '*', '|', ',', ' ']);
+            if !exact {
+                debug!("keeping version constraint {version} of local dependency");
+            }
+            exact
+        })
+        .collect()
+}
+
+impl VersionChanger for HelmChart {
+    /// `helm-chart:[field][,local-dependencies]@path` like `helm-chart:both,local-dependencies`
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let mut field = ChartField::Version;
+        let mut update_local_dependencies = false;
+        for option in info.into_iter().flat_map(|x| x.split(',')) {
+            match option {
+                "version" => field = ChartField::Version,
+                "appVersion" => field = ChartField::AppVersion,
+                "both" => field = ChartField::Both,
+                "local-dependencies" => update_local_dependencies = true,
+                option => panic!("invalid helm-chart version changer: unknown option {option}"),
+            }
+        }
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            field,
+            update_local_dependencies,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = self.read().await;
+        let document = YamlDocument::parse(&source).expect("parsing Chart.yaml");
+        let mut versions = self
+            .version_scalars(&document)
+            .into_iter()
+            .map(|x| document.scalar(x));
+        let version = versions.next().unwrap();
+        for other in versions {
+            if other != version {
+                panic!("version mismatch in Chart.yaml: version {version} != appVersion {other}");
+            }
+        }
+        version
+    }
+
+    async fn set_version(&self, version: &str) {
+        let source = self.read().await;
+        let mut document = YamlDocument::parse(&source).expect("parsing Chart.yaml");
+        let mut scalars = self.version_scalars(&document);
+        // local dependencies follow the chart version, not the app version
+        if self.update_local_dependencies && self.field.keys().contains(&"version") {
+            scalars.extend(local_dependency_versions(&document));
+        }
+        for scalar in scalars {
+            document.set_scalar(scalar, version);
+        }
+        tokio::fs::write(&self.path, document.to_string())
+            .await
+            .expect("writing Chart.yaml");
+    }
+}
+
+#[cfg(test)]
+const CHART: &str = r#"apiVersion: v2
+name: example
+description: A Helm chart for Kubernetes
+type: application
+# This is the chart version.
+version: 0.1.0
+# This is the version number of the application being deployed.
+appVersion: "0.1.0"
+dependencies:
+  - name: common
+    version: 0.1.0 # lockstep
+    repository: file://../common
+  - name: constrained
+    version: ~0.1.0
+    repository: "file://../constrained"
+  - name: redis
+    version: 0.1.0
+    repository: https://charts.example.com
+"#;
+
+#[tokio::test]
+async fn helm_chart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Chart.yaml");
+    std::fs::write(&path, CHART).unwrap();
+    let path = path.to_str().unwrap();
+
+    let changer = HelmChart::parse(Some("both,local-dependencies"), Some(path));
+    assert_eq!(changer.load_version().await, "0.1.0");
+    changer.set_version("0.2.0").await;
+    assert_eq!(changer.load_version().await, "0.2.0");
+
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        CHART
+            .replace("version: 0.1.0\n# This", "version: 0.2.0\n# This")
+            .replace("\"0.1.0\"", "\"0.2.0\"")
+            .replace("0.1.0 # lockstep", "0.2.0 # lockstep")
+    );
+}
+
+#[tokio::test]
+async fn single_field() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Chart.yaml");
+    std::fs::write(&path, CHART).unwrap();
+    let path = path.to_str().unwrap();
+
+    let changer = HelmChart::parse(Some("appVersion"), Some(path));
+    changer.set_version("1.0.0").await;
+    assert_eq!(changer.load_version().await, "1.0.0");
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        CHART.replace("\"0.1.0\"", "\"1.0.0\"")
+    );
+
+    // local dependencies are kept by default
+    let changer = HelmChart::parse(None, Some(path));
+    changer.set_version("1.0.0").await;
+    assert!(std::fs::read_to_string(path)
+        .unwrap()
+        .contains("version: 0.1.0 # lockstep"));
+}
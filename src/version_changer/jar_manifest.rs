@@ -0,0 +1,275 @@
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JarManifest {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    /// the header holding the version in the main section.
+    /// for `Bundle-Version`, the exact version is kept in `Implementation-Version`
+    /// if the header exists or the OSGi version cannot be converted back to the same version
+    #[serde(default = "header_default", alias = "info")]
+    header: String,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("META-INF/MANIFEST.MF")
+}
+
+fn header_default() -> String {
+    "Implementation-Version".to_owned()
+}
+
+/// the maximum length of a line in bytes excluding newline
+const MAX_LINE_BYTES: usize = 72;
+
+impl Display for JarManifest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "jar-manifest(at {} header {})",
+            self.path.display(),
+            self.header
+        )
+    }
+}
+
+impl JarManifest {
+    async fn read(&self) -> String {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", self.path.display(), e))
+    }
+
+    fn is_osgi(&self) -> bool {
+        self.header.eq_ignore_ascii_case("Bundle-Version")
+    }
+}
+
+impl VersionChanger for JarManifest {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            header: info.map(Into::into).unwrap_or_else(header_default),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let manifest = self.read().await;
+        let (_, value) = find_header(&manifest, &self.header)
+            .unwrap_or_else(|| panic!("no {} in {}", self.header, self.path.display()));
+        if !self.is_osgi() {
+            return value;
+        }
+        match find_header(&manifest, "Implementation-Version") {
+            Some((_, original)) if to_osgi_version(&original).as_ref() == Ok(&value) => original,
+            _ => from_osgi_version(&value),
+        }
+    }
+
+    async fn set_version(&self, version: &str) {
+        let mut manifest = self.read().await;
+        let (range, _) = find_header(&manifest, &self.header)
+            .unwrap_or_else(|| panic!("no {} in {}", self.header, self.path.display()));
+        let newline = if manifest.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        // keep the case of the header name in the file
+        let name = manifest[range.clone()]
+            .split(':')
+            .next()
+            .unwrap()
+            .to_owned();
+
+        let mut replacements = vec![];
+        if self.is_osgi() {
+            let value = to_osgi_version(version).unwrap_or_else(|e| panic!("{e}"));
+            let original = find_header(&manifest, "Implementation-Version");
+            match original {
+                Some((original, _)) => replacements.push((
+                    original,
+                    header_lines("Implementation-Version", version, newline),
+                )),
+                None if from_osgi_version(&value) != version => replacements.push((
+                    range.end..range.end,
+                    header_lines("Implementation-Version", version, newline),
+                )),
+                None => {}
+            }
+            replacements.push((range, header_lines(&name, &value, newline)));
+        } else {
+            replacements.push((range, header_lines(&name, version, newline)));
+        }
+
+        // replace later one first to keep the range of the other
+        replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        for (range, replacement) in replacements {
+            manifest.replace_range(range, &replacement);
+        }
+        tokio::fs::write(&self.path, manifest)
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+/// Finds the header in the main section.
+/// Returns the range of the header lines including the last newline and the value.
+fn find_header(manifest: &str, name: &str) -> Option<(Range<usize>, String)> {
+    let mut offset = 0;
+    let mut lines = manifest.split_inclusive('\n').peekable();
+    while let Some(line) = lines.next() {
+        let start = offset;
+        offset += line.len();
+        let content = line.trim_end_matches(['\r', '\n']);
+        if content.is_empty() {
+            // end of the main section
+            return None;
+        }
+        let Some((header, value)) = content.split_once(':') else {
+            continue;
+        };
+        if !header.eq_ignore_ascii_case(name) {
+            continue;
+        }
+
+        let mut value = value.strip_prefix(' ').unwrap_or(value).to_owned();
+        while let Some(continuation) = lines.next_if(|x| x.starts_with(' ')) {
+            offset += continuation.len();
+            value.push_str(continuation[1..].trim_end_matches(['\r', '\n']));
+        }
+        return Some((start..offset, value));
+    }
+    None
+}
+
+/// Creates the header lines wrapped at 72 bytes
+fn header_lines(name: &str, value: &str, newline: &str) -> String {
+    let header = format!("{name}: {value}");
+    let mut result = String::new();
+    let mut rest = header.as_str();
+    // the first line has 72 bytes and continuation lines have a space and 71 bytes
+    let mut max = MAX_LINE_BYTES;
+    loop {
+        let mut end = rest.len().min(max);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        result.push_str(&rest[..end]);
+        result.push_str(newline);
+        rest = &rest[end..];
+        if rest.is_empty() {
+            return result;
+        }
+        result.push(' ');
+        max = MAX_LINE_BYTES - 1;
+    }
+}
+
+/// Converts the version to OSGi version. `1.0-SNAPSHOT` => `1.0.0.qualifier`, `1.0.0-beta.1` => `1.0.0.beta_1`
+fn to_osgi_version(version: &str) -> Result<String, String> {
+    if version.contains('+') {
+        return Err(format!(
+            "build metadata of {version} cannot be represented in Bundle-Version"
+        ));
+    }
+    let (release, prerelease) = match version.split_once('-') {
+        Some((release, prerelease)) => (release, Some(prerelease)),
+        None => (version, None),
+    };
+    let mut parts = release.split('.').collect::<Vec<_>>();
+    if parts.len() > 3 || parts.iter().any(|x| x.parse::<u32>().is_err()) {
+        return Err(format!("version {version} cannot be an OSGi version"));
+    }
+    parts.resize(3, "0");
+    let release = parts.join(".");
+    match prerelease {
+        None => Ok(release),
+        Some("SNAPSHOT") => Ok(format!("{release}.qualifier")),
+        Some(prerelease) => {
+            // qualifier allows alphanumerics, `_` and `-`
+            if !prerelease
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            {
+                return Err(format!(
+                    "prerelease of {version} cannot be an OSGi qualifier"
+                ));
+            }
+            Ok(format!("{release}.{}", prerelease.replace('.', "_")))
+        }
+    }
+}
+
+/// Converts OSGi version back. `1.0.0.qualifier` => `1.0.0-SNAPSHOT`
+fn from_osgi_version(version: &str) -> String {
+    let parts = version.splitn(4, '.').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [major, minor, micro, "qualifier"] => format!("{major}.{minor}.{micro}-SNAPSHOT"),
+        [major, minor, micro, qualifier] => {
+            format!("{major}.{minor}.{micro}-{}", qualifier.replace('_', "."))
+        }
+        _ => version.to_owned(),
+    }
+}
+
+#[test]
+fn osgi_version() {
+    assert_eq!(to_osgi_version("1.0-SNAPSHOT").unwrap(), "1.0.0.qualifier");
+    assert_eq!(to_osgi_version("1.2.3-beta.1").unwrap(), "1.2.3.beta_1");
+    assert_eq!(to_osgi_version("1.2.3-rc-1_a").unwrap(), "1.2.3.rc-1_a");
+    assert_eq!(to_osgi_version("1.2.3").unwrap(), "1.2.3");
+    assert!(to_osgi_version("1.2.3+build").is_err());
+    assert_eq!(from_osgi_version("1.0.0.qualifier"), "1.0.0-SNAPSHOT");
+    assert_eq!(from_osgi_version("1.2.3.beta_1"), "1.2.3-beta.1");
+    assert_eq!(from_osgi_version("1.2.3"), "1.2.3");
+}
+
+#[tokio::test]
+async fn jar_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("MANIFEST.MF");
+    let long = format!("1.0.0-{}", "a".repeat(70));
+    let source = format!(
+        "Manifest-Version: 1.0\r\nImplementation-Version: {}\r\n {}\r\nBundle-Version: 1.0.0.qualifier\r\n\r\nName: example/\r\nImplementation-Version: 0.0.0\r\n",
+        &long[..48],
+        &long[48..]
+    );
+    std::fs::write(&path, &source).unwrap();
+    let path_str = path.to_str().unwrap();
+
+    let implementation = JarManifest::parse(None, Some(path_str));
+    assert_eq!(implementation.load_version().await, long);
+    implementation.set_version("1.1.0-SNAPSHOT").await;
+    assert_eq!(implementation.load_version().await, "1.1.0-SNAPSHOT");
+
+    let bundle = JarManifest::parse(Some("Bundle-Version"), Some(path_str));
+    assert_eq!(bundle.load_version().await, "1.0.0-SNAPSHOT");
+    bundle.set_version("1.1.0-SNAPSHOT").await;
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Manifest-Version: 1.0\r\nImplementation-Version: 1.1.0-SNAPSHOT\r\nBundle-Version: 1.1.0.qualifier\r\n\r\nName: example/\r\nImplementation-Version: 0.0.0\r\n"
+    );
+
+    // lossy conversion keeps the original in Implementation-Version
+    std::fs::write(&path, "Manifest-Version: 1.0\nBundle-Version: 1.0.0\n").unwrap();
+    bundle.set_version("1.0-SNAPSHOT").await;
+    assert_eq!(bundle.load_version().await, "1.0-SNAPSHOT");
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Manifest-Version: 1.0\nBundle-Version: 1.0.0.qualifier\nImplementation-Version: 1.0-SNAPSHOT\n"
+    );
+    bundle.set_version("1.0.1").await;
+    assert_eq!(bundle.load_version().await, "1.0.1");
+
+    let long = "1".repeat(100);
+    implementation.set_version(&long).await;
+    let manifest = std::fs::read_to_string(&path).unwrap();
+    assert!(manifest.lines().all(|x| x.len() <= MAX_LINE_BYTES));
+    assert_eq!(implementation.load_version().await, long);
+}
@@ -0,0 +1,324 @@
+use crate::utils::json::{parse_json, quote_string, JsonValue, Punctuation, Token};
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonPointer {
+    path: PathBuf,
+    /// RFC 6901 JSON pointer like `/info/version`
+    #[serde(alias = "info")]
+    pointer: String,
+    /// if true, missing object keys are created on setting version.
+    /// `create:` before the pointer in the changer string
+    #[serde(default)]
+    create: bool,
+}
+
+impl Display for JsonPointer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "json-pointer(at {} pointer {})",
+            self.path.display(),
+            self.pointer
+        )
+    }
+}
+
+fn parse_pointer(pointer: &str) -> Vec<String> {
+    if pointer.is_empty() {
+        return Vec::new();
+    }
+    let Some(pointer) = pointer.strip_prefix('/') else {
+        panic!("json pointer must start with '/': {pointer}");
+    };
+    pointer
+        .split('/')
+        .map(|x| x.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+impl VersionChanger for JsonPointer {
+    /// `json-pointer:[create:]pointer@path`
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let pointer = info.expect("json-pointer needs json pointer");
+        let (create, pointer) = match pointer.strip_prefix("create:") {
+            Some(pointer) => (true, pointer),
+            None => (false, pointer),
+        };
+        parse_pointer(pointer);
+        Self {
+            path: path.expect("json-pointer needs path").into(),
+            pointer: pointer.to_owned(),
+            create,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = tokio::fs::read_to_string(&self.path)
+            .await
+            .expect("reading json file");
+        serde_json::from_str::<serde_json::Value>(&source)
+            .expect("parsing json file")
+            .pointer(&self.pointer)
+            .unwrap_or_else(|| panic!("no value at {} in json file", self.pointer))
+            .as_str()
+            .unwrap_or_else(|| panic!("value at {} in json file is not a string", self.pointer))
+            .to_owned()
+    }
+
+    async fn set_version(&self, version: &str) {
+        let source = tokio::fs::read_to_string(&self.path)
+            .await
+            .expect("reading json file");
+        let updated = set_pointer(&source, &parse_pointer(&self.pointer), version, self.create);
+        tokio::fs::write(&self.path, updated)
+            .await
+            .expect("writing json file");
+    }
+}
+
+enum Target<'a, 'src> {
+    Found(&'a mut JsonValue<'src>),
+    /// the deepest existing value, the depth of it, and the keys not found
+    Missing(&'a JsonValue<'src>, usize, &'a [String]),
+}
+
+fn navigate<'a, 'src>(
+    value: &'a mut JsonValue<'src>,
+    keys: &'a [String],
+    depth: usize,
+) -> Target<'a, 'src> {
+    let Some((key, rest)) = keys.split_first() else {
+        return Target::Found(value);
+    };
+    let index = match &*value {
+        JsonValue::Object(object) => object.members.iter().position(|member| match member.key {
+            Token::StringLiteral(quoted) => serde_json::from_str::<String>(quoted).unwrap() == *key,
+            _ => false,
+        }),
+        JsonValue::Array(array) => key
+            .parse::<usize>()
+            .ok()
+            .filter(|&x| x < array.elements.len()),
+        JsonValue::Literal(_) => {
+            panic!("the parent of {key} in json file is not an object nor an array")
+        }
+    };
+    match (index, value) {
+        (Some(index), JsonValue::Object(object)) => {
+            navigate(&mut object.members[index].value, rest, depth + 1)
+        }
+        (Some(index), JsonValue::Array(array)) => {
+            navigate(&mut array.elements[index].0, rest, depth + 1)
+        }
+        (_, value) => Target::Missing(value, depth, keys),
+    }
+}
+
+/// Returns the json with the string at the path set.
+/// If `create` is true, missing keys are inserted with the indentation inferred from the document.
+fn set_pointer(source: &str, keys: &[String], version: &str, create: bool) -> String {
+    let quoted = quote_string(version);
+    let mut parsed = parse_json(source).expect("parsing json file");
+    let style = Style::infer(source, &parsed.value);
+
+    let (parent, depth, missing) = match navigate(&mut parsed.value, keys, 0) {
+        Target::Found(value) => {
+            if !matches!(value, JsonValue::Literal(Token::StringLiteral(_))) {
+                panic!("value at /{} in json file is not a string", keys.join("/"));
+            }
+            *value = JsonValue::Literal(Token::StringLiteral(&quoted));
+            return parsed.to_string();
+        }
+        Target::Missing(parent, depth, missing) => (parent, depth, missing),
+    };
+    if !create {
+        panic!("no value at /{} in json file", keys.join("/"));
+    }
+
+    let (open, close, is_empty, element) = match parent {
+        JsonValue::Object(object) => {
+            let key = quote_string(&missing[0]);
+            let value = style.value(&missing[1..], &quoted, depth + 1);
+            let member = format!("{}{}{}", key, style.colon, value);
+            (
+                &object.open_brace,
+                &object.close_brace,
+                object.members.is_empty(),
+                member,
+            )
+        }
+        JsonValue::Array(array) => {
+            if missing[0] == "-" {
+                // appending on every release is not idempotent, and `-` cannot be read
+                panic!(
+                    "`-` of json pointer cannot be used for version. use the index of the element"
+                );
+            }
+            if missing[0] != array.elements.len().to_string() {
+                panic!("index {} is out of range of array in json file", missing[0]);
+            }
+            let value = style.value(&missing[1..], &quoted, depth + 1);
+            (
+                &array.open_bracket,
+                &array.close_bracket,
+                array.elements.is_empty(),
+                value,
+            )
+        }
+        JsonValue::Literal(_) => unreachable!(),
+    };
+    let (Token::Punctuation(_, open), Token::Punctuation(_, close)) = (open, close) else {
+        unreachable!()
+    };
+
+    // insert before the whitespace of closing punctuation.
+    // the whitespace may belong to the closing punctuation or the last element
+    let close =
+        close.as_ptr() as usize - source.as_ptr() as usize + close.find(['}', ']']).unwrap();
+    let position = source[..close].trim_end().len();
+    let insertion = if is_empty {
+        format!("{}{}{}", style.line(depth + 1), element, style.line(depth))
+    } else {
+        // the separator after the opening punctuation is used for the new element
+        format!(",{}{}", &open[1..], element)
+    };
+    format!(
+        "{}{}{}",
+        &source[..position],
+        insertion,
+        &source[position..]
+    )
+}
+
+/// The formatting style of the json document
+struct Style {
+    /// None for compact single line json
+    newline: Option<&'static str>,
+    indent: String,
+    colon: String,
+}
+
+impl Style {
+    fn infer(source: &str, root: &JsonValue) -> Self {
+        let newline = if source.contains("\r\n") {
+            Some("\r\n")
+        } else if source.trim().contains('\n') {
+            Some("\n")
+        } else {
+            None
+        };
+        let (open, colon) = match root {
+            JsonValue::Object(object) => (
+                Some(&object.open_brace),
+                object.members.first().map(|x| &x.colon),
+            ),
+            JsonValue::Array(array) => (Some(&array.open_bracket), None),
+            JsonValue::Literal(_) => (None, None),
+        };
+        let indent = match open {
+            Some(Token::Punctuation(_, open)) if open.contains('\n') => {
+                open.rsplit('\n').next().unwrap().to_owned()
+            }
+            _ => "  ".to_owned(),
+        };
+        let colon = match colon {
+            Some(Token::Punctuation(Punctuation::Colon, colon)) => colon.to_string(),
+            _ if newline.is_some() => ": ".to_owned(),
+            _ => ":".to_owned(),
+        };
+        Self {
+            newline,
+            indent,
+            colon,
+        }
+    }
+
+    /// Returns newline and indentation for the depth
+    fn line(&self, depth: usize) -> String {
+        match self.newline {
+            Some(newline) => format!("{}{}", newline, self.indent.repeat(depth)),
+            None => String::new(),
+        }
+    }
+
+    /// Creates nested objects with keys holding the value
+    fn value(&self, keys: &[String], quoted: &str, depth: usize) -> String {
+        match keys.split_first() {
+            None => quoted.to_owned(),
+            Some((key, rest)) => format!(
+                "{{{}{}{}{}{}}}",
+                self.line(depth + 1),
+                quote_string(key),
+                self.colon,
+                self.value(rest, quoted, depth + 1),
+                self.line(depth),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+fn keys(pointer: &str) -> Vec<String> {
+    parse_pointer(pointer)
+}
+
+#[test]
+fn set_existing() {
+    let source = "{\n  \"openapi\": \"3.0.0\",\n  \"info\": {\n    \"title\": \"a/b\",\n    \"version\": \"1.0.0\"\n  },\n  \"packages\": [{ \"version\": \"1.0.0\" }]\n}\n";
+    assert_eq!(
+        set_pointer(source, &keys("/info/version"), "1.1.0", false),
+        source.replacen("\"1.0.0\"", "\"1.1.0\"", 1)
+    );
+    assert_eq!(
+        set_pointer(source, &keys("/packages/0/version"), "1.1.0", false),
+        source.replace("{ \"version\": \"1.0.0\" }", "{ \"version\": \"1.1.0\" }")
+    );
+    assert_eq!(keys("/a~1b/c~0d"), ["a/b", "c~d"]);
+}
+
+#[test]
+fn create_missing() {
+    let source = "{\n    \"name\": \"example\",\n    \"meta\": {}\n}\n";
+    assert_eq!(
+        set_pointer(source, &keys("/version"), "1.0.0", true),
+        "{\n    \"name\": \"example\",\n    \"meta\": {},\n    \"version\": \"1.0.0\"\n}\n"
+    );
+    assert_eq!(
+        set_pointer(source, &keys("/meta/release/version"), "1.0.0", true),
+        "{\n    \"name\": \"example\",\n    \"meta\": {\n        \"release\": {\n            \"version\": \"1.0.0\"\n        }\n    }\n}\n"
+    );
+
+    let compact = r#"{"name":"example","versions":[]}"#;
+    assert_eq!(
+        set_pointer(compact, &keys("/versions/0"), "1.0.0", true),
+        r#"{"name":"example","versions":["1.0.0"]}"#
+    );
+    assert_eq!(
+        set_pointer(compact, &keys("/info/version"), "1.0.0", true),
+        r#"{"name":"example","versions":[],"info":{"version":"1.0.0"}}"#
+    );
+}
+
+#[test]
+#[should_panic(expected = "`-` of json pointer cannot be used")]
+fn append_to_array() {
+    set_pointer(r#"{"versions":[]}"#, &keys("/versions/-"), "1.0.0", true);
+}
+
+#[test]
+fn create_in_changer_string() {
+    let changer = JsonPointer::parse(Some("create:/info/version"), Some("openapi.json"));
+    assert!(changer.create);
+    assert_eq!(changer.pointer, "/info/version");
+    assert!(!JsonPointer::parse(Some("/version"), Some("a.json")).create);
+}
+
+#[test]
+#[should_panic(expected = "no value at /info/version")]
+fn missing_without_create() {
+    set_pointer(r#"{"info":{}}"#, &keys("/info/version"), "1.0.0", false);
+}
@@ -0,0 +1,224 @@
+use crate::utils::xml::{ElementId, XmlDocument};
+use crate::version_changer::VersionChanger;
+use log::debug;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MavenPom {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    /// if true, `<parent><version>` of modules in the reactor are also updated
+    #[serde(default = "update_modules_default")]
+    update_modules: bool,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("pom.xml")
+}
+
+fn update_modules_default() -> bool {
+    true
+}
+
+impl Display for MavenPom {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "maven-pom(at {})", self.path.display())
+    }
+}
+
+impl VersionChanger for MavenPom {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        if info.is_some() {
+            panic!("invalid maven-pom version changer");
+        }
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            update_modules: update_modules_default(),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = read_pom(&self.path).await;
+        let pom = XmlDocument::parse(&source).expect("parsing pom.xml");
+        let holder = version_holder(&pom);
+        pom.text(holder)
+            .expect("version in pom.xml is not a text")
+            .trim()
+            .to_string()
+    }
+
+    async fn set_version(&self, version: &str) {
+        let source = read_pom(&self.path).await;
+        let mut pom = XmlDocument::parse(&source).expect("parsing pom.xml");
+        let holder = version_holder(&pom);
+        pom.set_text(holder, version);
+        tokio::fs::write(&self.path, pom.to_string())
+            .await
+            .expect("writing pom.xml");
+
+        if self.update_modules {
+            update_modules(&self.path, &pom, version).await;
+        }
+    }
+}
+
+async fn read_pom(path: &Path) -> String {
+    tokio::fs::read_to_string(path)
+        .await
+        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e))
+}
+
+/// Returns the element actually holding the version.
+/// With CI-friendly versions, this is the property referenced by `<version>`.
+fn version_holder(pom: &XmlDocument) -> ElementId {
+    let version = pom.find(&["version"]).expect("no <version> in pom.xml");
+    let text = pom
+        .text(version)
+        .expect("<version> in pom.xml is not a text");
+    let text = text.trim();
+
+    if !text.contains("${") {
+        return version;
+    }
+
+    let Some(property) = property_reference(text) else {
+        panic!("version expression {text} in pom.xml is not supported; only single property reference like ${{revision}} is supported");
+    };
+
+    pom.find(&["properties", property])
+        .unwrap_or_else(|| panic!("property {property} for version is not defined in pom.xml"))
+}
+
+fn property_reference(text: &str) -> Option<&str> {
+    text.strip_prefix("${")?
+        .strip_suffix('}')
+        .filter(|name| !name.contains(['$', '{', '}']))
+}
+
+/// (groupId, artifactId) of the project
+fn coordinates(pom: &XmlDocument) -> (String, String) {
+    let text = |path: &[&str]| pom.find(path).and_then(|x| pom.text(x));
+    let group_id = text(&["groupId"])
+        .or_else(|| text(&["parent", "groupId"]))
+        .expect("no groupId in pom.xml");
+    let artifact_id = text(&["artifactId"]).expect("no artifactId in pom.xml");
+    (group_id.trim().to_owned(), artifact_id.trim().to_owned())
+}
+
+fn module_paths<'a>(
+    pom_path: &'a Path,
+    pom: &'a XmlDocument,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    let base = pom_path.parent().expect("pom.xml must have parent");
+    pom.find(&["modules"])
+        .into_iter()
+        .flat_map(|modules| pom.children(modules))
+        .filter(|&module| pom.name(module) == "module")
+        .filter_map(|module| pom.text(module))
+        .map(|module| base.join(module.trim()))
+}
+
+async fn update_modules(root_path: &Path, root: &XmlDocument<'_>, version: &str) {
+    // (module pom path, coordinates of the parent)
+    let mut queue = module_paths(root_path, root)
+        .map(|path| (path, coordinates(root)))
+        .collect::<Vec<_>>();
+
+    while let Some((mut path, parent_coordinates)) = queue.pop() {
+        if tokio::fs::metadata(&path)
+            .await
+            .map(|x| x.is_dir())
+            .unwrap_or(false)
+        {
+            path.push("pom.xml");
+        }
+
+        let source = read_pom(&path).await;
+        let mut pom = XmlDocument::parse(&source)
+            .unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e));
+
+        let Some(parent) = pom.find(&["parent"]) else {
+            continue;
+        };
+        let text = |name: &str| pom.child(parent, name).and_then(|x| pom.text(x));
+        let refers_parent = text("groupId").as_deref().map(str::trim)
+            == Some(parent_coordinates.0.as_str())
+            && text("artifactId").as_deref().map(str::trim) == Some(parent_coordinates.1.as_str());
+        if !refers_parent {
+            debug!(
+                "{} is not a child of {:?}",
+                path.display(),
+                parent_coordinates
+            );
+            continue;
+        }
+
+        // the version inherited from the parent changes so modules of this module also need update
+        if pom.find(&["version"]).is_none() {
+            let coordinates = coordinates(&pom);
+            queue.extend(module_paths(&path, &pom).map(|module| (module, coordinates.clone())));
+        }
+
+        let Some(parent_version) = pom.child(parent, "version") else {
+            continue;
+        };
+        if text("version").is_some_and(|x| x.contains("${")) {
+            // CI-friendly version, the property is updated by the root pom
+            continue;
+        }
+
+        debug!("updating parent version of {}", path.display());
+        pom.set_text(parent_version, version);
+        tokio::fs::write(&path, pom.to_string())
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+    }
+}
+
+#[tokio::test]
+async fn reactor() {
+    let dir = super::copy_fixture("maven-pom/reactor");
+    let root = dir.path().join("pom.xml");
+    let changer = MavenPom::parse(None, Some(root.to_str().unwrap()));
+
+    assert_eq!(changer.load_version().await, "1.0.0-SNAPSHOT");
+    changer.set_version("1.0.0").await;
+    assert_eq!(changer.load_version().await, "1.0.0");
+
+    let root = std::fs::read_to_string(&root).unwrap();
+    assert!(
+        root.contains("  <!-- the version of the whole reactor -->\n  <version>1.0.0</version>\n")
+    );
+
+    let core = std::fs::read_to_string(dir.path().join("core/pom.xml")).unwrap();
+    assert!(core.contains("<artifactId>reactor</artifactId>\n    <version>1.0.0</version>\n"));
+    // dependency version is not changed
+    assert!(core
+        .contains("<artifactId>example</artifactId>\n      <version>1.0.0-SNAPSHOT</version>\n"));
+
+    let app = std::fs::read_to_string(dir.path().join("app/pom.xml")).unwrap();
+    assert!(app.contains("<artifactId>reactor</artifactId>\n    <version>1.0.0</version>\n"));
+
+    let cli = std::fs::read_to_string(dir.path().join("app/cli/pom.xml")).unwrap();
+    assert!(cli.contains("<artifactId>app</artifactId>\n    <version>1.0.0</version>\n"));
+}
+
+#[tokio::test]
+async fn ci_friendly() {
+    let dir = super::copy_fixture("maven-pom/ci-friendly");
+    let root = dir.path().join("pom.xml");
+    let changer = MavenPom::parse(None, Some(root.to_str().unwrap()));
+
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0-SNAPSHOT").await;
+    assert_eq!(changer.load_version().await, "1.1.0-SNAPSHOT");
+
+    let root = std::fs::read_to_string(&root).unwrap();
+    assert!(root.contains("<version>${revision}</version>"));
+    assert!(root.contains("<revision>1.1.0-SNAPSHOT</revision>"));
+
+    let module = std::fs::read_to_string(dir.path().join("module/pom.xml")).unwrap();
+    assert!(module.contains("<version>${revision}</version>"));
+}
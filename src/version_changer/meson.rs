@@ -0,0 +1,229 @@
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Sets the `version` keyword argument of `project()` in `meson.build`
+#[derive(Debug, Deserialize)]
+pub(crate) struct Meson {
+    #[serde(default = "path_default", alias = "info")]
+    path: PathBuf,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("meson.build")
+}
+
+impl Display for Meson {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "meson(at {})", self.path.display())
+    }
+}
+
+impl Meson {
+    async fn read(&self) -> String {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", self.path.display(), e))
+    }
+
+    /// Returns the range of the string literal for version including quotes
+    fn find_version(&self, source: &str) -> Range<usize> {
+        let tokens =
+            tokenize(source).unwrap_or_else(|e| panic!("parsing {}: {}", self.path.display(), e));
+        find_project_version(source, &tokens)
+            .unwrap_or_else(|e| panic!("{e} in {}", self.path.display()))
+    }
+}
+
+impl VersionChanger for Meson {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        Self {
+            path: path.or(info).map(Into::into).unwrap_or_else(path_default),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = self.read().await;
+        let literal = &source[self.find_version(&source)];
+        unescape(&literal[1..literal.len() - 1])
+    }
+
+    async fn set_version(&self, version: &str) {
+        let mut source = self.read().await;
+        let range = self.find_version(&source);
+        let literal = escapes!(version, @prefix = "'", @suffix = "'", '\'' => "\\'", '\\' => "\\\\", '\n' => "\\n");
+        source.replace_range(range, &literal);
+        tokio::fs::write(&self.path, source)
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum TokenKind {
+    Identifier,
+    /// single-line string literal without prefix
+    String,
+    /// multiline or format string
+    OtherString,
+    Number,
+    Punct,
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: TokenKind,
+    range: Range<usize>,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(&c) = bytes.get(pos) {
+        let start = pos;
+        let kind = match c {
+            b'#' => {
+                pos = source[pos..].find('\n').map_or(bytes.len(), |x| pos + x);
+                continue;
+            }
+            c if c.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            b'\'' | b'f'
+                if source[pos..].starts_with("'''") || source[pos..].starts_with("f'''") =>
+            {
+                pos += source[pos..].find("'''").unwrap() + 3;
+                pos = source[pos..]
+                    .find("'''")
+                    .map(|x| pos + x + 3)
+                    .ok_or("unclosed multiline string")?;
+                TokenKind::OtherString
+            }
+            b'\'' | b'f' if source[pos..].starts_with('\'') || source[pos..].starts_with("f'") => {
+                let kind = if c == b'f' {
+                    pos += 1;
+                    TokenKind::OtherString
+                } else {
+                    TokenKind::String
+                };
+                pos += 1;
+                loop {
+                    match bytes.get(pos) {
+                        None | Some(b'\n') => return Err("unclosed string".to_owned()),
+                        Some(b'\\') => pos += 2,
+                        Some(b'\'') => break,
+                        Some(_) => pos += 1,
+                    }
+                }
+                pos += 1;
+                kind
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while bytes
+                    .get(pos)
+                    .is_some_and(|x| x.is_ascii_alphanumeric() || *x == b'_')
+                {
+                    pos += 1;
+                }
+                TokenKind::Identifier
+            }
+            c if c.is_ascii_digit() => {
+                while bytes.get(pos).is_some_and(u8::is_ascii_alphanumeric) {
+                    pos += 1;
+                }
+                TokenKind::Number
+            }
+            _ => {
+                // multibyte punctuations like `==` don't matter here
+                pos += source[pos..].chars().next().unwrap().len_utf8();
+                TokenKind::Punct
+            }
+        };
+        tokens.push(Token {
+            kind,
+            range: start..pos,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Finds `version: '...'` in the arguments of the first `project()` call
+fn find_project_version(source: &str, tokens: &[Token]) -> Result<Range<usize>, String> {
+    let text = |i: usize| tokens.get(i).map_or("", |x| &source[x.range.clone()]);
+    let project = (0..tokens.len())
+        .find(|&i| {
+            tokens[i].kind == TokenKind::Identifier && text(i) == "project" && text(i + 1) == "("
+        })
+        .ok_or("no project()")?;
+
+    let mut depth = 0;
+    for i in project + 1..tokens.len() {
+        match text(i) {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            "version" if depth == 1 && text(i + 1) == ":" && matches!(text(i - 1), "(" | ",") => {
+                let value = tokens.get(i + 2).ok_or("unclosed project(")?;
+                if value.kind != TokenKind::String || !matches!(text(i + 3), "," | ")") {
+                    return Err(format!(
+                        "version of project() is not a string literal: {}",
+                        text(i + 2)
+                    ));
+                }
+                return Ok(value.range.clone());
+            }
+            _ => {}
+        }
+    }
+    Err("no version in project()".to_owned())
+}
+
+fn unescape(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(c) => result.push(c),
+                None => {}
+            },
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[tokio::test]
+async fn meson_build() {
+    let dir = super::copy_fixture("meson");
+    let path = dir.path().join("meson.build");
+    let source = std::fs::read_to_string(&path).unwrap();
+    let path = path.to_str();
+
+    let changer = Meson::parse(None, path);
+    assert_eq!(changer.load_version().await, "1.2.3");
+    changer.set_version("1.3.0-rc.1").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        source.replace("'1.2.3'", "'1.3.0-rc.1'")
+    );
+    assert_eq!(changer.load_version().await, "1.3.0-rc.1");
+}
+
+#[tokio::test]
+#[should_panic(expected = "version of project() is not a string literal: files")]
+async fn meson_version_file() {
+    let dir = super::copy_fixture("meson/version-file");
+    let path = dir.path().join("meson.build");
+    Meson::parse(None, path.to_str()).load_version().await;
+}
@@ -0,0 +1,318 @@
+//! Version changers for metadata files of Minecraft mods and plugins
+
+use crate::utils;
+use crate::utils::yaml::YamlDocument;
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+
+const RESOURCES: &str = "src/main/resources";
+
+/// Returns the first existing path in candidates, or the last one if none exists
+async fn find_existing(candidates: &[&str]) -> PathBuf {
+    for candidate in candidates {
+        let path = Path::new(RESOURCES).join(candidate);
+        if tokio::fs::try_exists(&path)
+            .await
+            .unwrap_or_else(|e| panic!("checking {}: {}", path.display(), e))
+        {
+            return path;
+        }
+    }
+    Path::new(RESOURCES).join(candidates.last().unwrap())
+}
+
+/// Panics if the version is a placeholder expanded at build time like `${version}`
+fn check_placeholder(version: &str, file: &str) {
+    let placeholder = version.contains("${")
+        || version.starts_with('$')
+        || (version.len() > 1 && version.starts_with('@') && version.ends_with('@'));
+    if placeholder {
+        panic!("version in {file} is a placeholder {version} expanded at build time; change the version of the build script instead");
+    }
+}
+
+/// `fabric.mod.json` or `quilt.mod.json`
+#[derive(Debug, Deserialize)]
+pub(crate) struct FabricModJson {
+    /// if not specified, quilt.mod.json or fabric.mod.json in src/main/resources is used
+    path: Option<PathBuf>,
+}
+
+impl Display for FabricModJson {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "fabric-mod-json(at {})", path.display()),
+            None => write!(f, "fabric-mod-json"),
+        }
+    }
+}
+
+impl FabricModJson {
+    async fn path(&self) -> PathBuf {
+        match &self.path {
+            Some(path) => path.clone(),
+            None => find_existing(&["quilt.mod.json", "fabric.mod.json"]).await,
+        }
+    }
+}
+
+impl VersionChanger for FabricModJson {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        if info.is_some() {
+            panic!("invalid fabric-mod-json version changer");
+        }
+        Self {
+            path: path.map(Into::into),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = tokio::fs::read_to_string(self.path().await)
+            .await
+            .expect("reading mod json");
+        let json = serde_json::from_str::<serde_json::Value>(&source).expect("parsing mod json");
+        // quilt.mod.json has the version in quilt_loader object
+        let holder = json.get("quilt_loader").unwrap_or(&json);
+        let version = holder
+            .get("version")
+            .expect("no version in mod json")
+            .as_str()
+            .expect("version in mod json is not a string");
+        check_placeholder(version, "mod json");
+        version.to_owned()
+    }
+
+    async fn set_version(&self, version: &str) {
+        // checks placeholder
+        self.load_version().await;
+
+        let path = self.path().await;
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .expect("reading mod json");
+        let quoted = utils::json::quote_string(version);
+        let mut parsed = utils::json::parse_json(&source).expect("parsing mod json");
+        let mut object = parsed
+            .value
+            .as_object_mut()
+            .expect("mod json is not an object");
+        if object.get_mut(r#""quilt_loader""#).is_some() {
+            object = object
+                .get_mut(r#""quilt_loader""#)
+                .unwrap()
+                .as_object_mut()
+                .expect("quilt_loader is not an object");
+        }
+        object.set(r#""version""#, utils::json::Token::StringLiteral(&quoted));
+        tokio::fs::write(&path, parsed.to_string())
+            .await
+            .expect("writing mod json");
+    }
+}
+
+/// `META-INF/mods.toml` of Forge or `META-INF/neoforge.mods.toml` of NeoForge
+#[derive(Debug, Deserialize)]
+pub(crate) struct ModsToml {
+    /// if not specified, META-INF/neoforge.mods.toml or META-INF/mods.toml in src/main/resources is used
+    path: Option<PathBuf>,
+    /// the modId of `[[mods]]` entry. required if there are multiple mods
+    #[serde(alias = "info")]
+    mod_id: Option<String>,
+}
+
+impl Display for ModsToml {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mods-toml")?;
+        match (&self.path, &self.mod_id) {
+            (Some(path), Some(mod_id)) => write!(f, "(at {} mod {})", path.display(), mod_id),
+            (Some(path), None) => write!(f, "(at {})", path.display()),
+            (None, Some(mod_id)) => write!(f, "(mod {})", mod_id),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl ModsToml {
+    async fn path(&self) -> PathBuf {
+        match &self.path {
+            Some(path) => path.clone(),
+            None => find_existing(&["META-INF/neoforge.mods.toml", "META-INF/mods.toml"]).await,
+        }
+    }
+
+    fn version_value<'a>(&self, document: &'a mut DocumentMut) -> &'a mut toml_edit::Value {
+        let mods = document
+            .get_mut("mods")
+            .and_then(|x| x.as_array_of_tables_mut())
+            .expect("no [[mods]] in mods.toml");
+        let mut mods = mods.iter_mut().filter(|table| match &self.mod_id {
+            Some(mod_id) => table.get("modId").and_then(|x| x.as_str()) == Some(mod_id),
+            None => true,
+        });
+        let table = match (mods.next(), mods.next()) {
+            (Some(table), None) => table,
+            (None, _) => panic!("no [[mods]] entry for the mod in mods.toml"),
+            (Some(_), Some(_)) => panic!("multiple [[mods]] in mods.toml; specify modId"),
+        };
+        table
+            .get_mut("version")
+            .and_then(|x| x.as_value_mut())
+            .filter(|x| x.is_str())
+            .expect("version in mods.toml is not a string")
+    }
+}
+
+impl VersionChanger for ModsToml {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        Self {
+            path: path.map(Into::into),
+            mod_id: info.map(Into::into),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let mut document: DocumentMut = tokio::fs::read_to_string(self.path().await)
+            .await
+            .expect("reading mods.toml")
+            .parse()
+            .expect("parsing mods.toml");
+        let version = self.version_value(&mut document).as_str().unwrap();
+        check_placeholder(version, "mods.toml");
+        version.to_owned()
+    }
+
+    async fn set_version(&self, version: &str) {
+        let path = self.path().await;
+        let mut document: DocumentMut = tokio::fs::read_to_string(&path)
+            .await
+            .expect("reading mods.toml")
+            .parse()
+            .expect("parsing mods.toml");
+        let value = self.version_value(&mut document);
+        check_placeholder(value.as_str().unwrap(), "mods.toml");
+        utils::toml::set_string(value, version);
+        tokio::fs::write(&path, document.to_string())
+            .await
+            .expect("writing mods.toml");
+    }
+}
+
+/// `plugin.yml` of Bukkit or `paper-plugin.yml` of Paper
+#[derive(Debug, Deserialize)]
+pub(crate) struct PluginYml {
+    /// if not specified, paper-plugin.yml or plugin.yml in src/main/resources is used
+    path: Option<PathBuf>,
+}
+
+impl Display for PluginYml {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "plugin-yml(at {})", path.display()),
+            None => write!(f, "plugin-yml"),
+        }
+    }
+}
+
+impl PluginYml {
+    async fn path(&self) -> PathBuf {
+        match &self.path {
+            Some(path) => path.clone(),
+            None => find_existing(&["paper-plugin.yml", "plugin.yml"]).await,
+        }
+    }
+}
+
+fn plugin_version(document: &YamlDocument) -> std::ops::Range<usize> {
+    let version = document
+        .root()
+        .get("version")
+        .expect("no version in plugin.yml")
+        .as_scalar()
+        .expect("version in plugin.yml is not a scalar");
+    check_placeholder(&document.scalar(version.clone()), "plugin.yml");
+    version
+}
+
+impl VersionChanger for PluginYml {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        if info.is_some() {
+            panic!("invalid plugin-yml version changer");
+        }
+        Self {
+            path: path.map(Into::into),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = tokio::fs::read_to_string(self.path().await)
+            .await
+            .expect("reading plugin.yml");
+        let document = YamlDocument::parse(&source).expect("parsing plugin.yml");
+        document.scalar(plugin_version(&document))
+    }
+
+    async fn set_version(&self, version: &str) {
+        let path = self.path().await;
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .expect("reading plugin.yml");
+        let mut document = YamlDocument::parse(&source).expect("parsing plugin.yml");
+        document.set_scalar(plugin_version(&document), version);
+        tokio::fs::write(&path, document.to_string())
+            .await
+            .expect("writing plugin.yml");
+    }
+}
+
+/// Sets the version of the file in the copied `minecraft-mod` fixture.
+/// Returns the original and the updated content
+#[cfg(test)]
+async fn test_changer<C: VersionChanger>(file: &str, info: Option<&str>) -> (String, String) {
+    let dir = super::copy_fixture("minecraft-mod");
+    let path = dir.path().join(file);
+    let source = std::fs::read_to_string(&path).unwrap();
+    let changer = C::parse(info, path.to_str());
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0+mc1.20.1").await;
+    assert_eq!(changer.load_version().await, "1.1.0+mc1.20.1");
+    (source, std::fs::read_to_string(&path).unwrap())
+}
+
+#[tokio::test]
+async fn fabric_and_quilt() {
+    let (fabric, updated) = test_changer::<FabricModJson>("fabric.mod.json", None).await;
+    assert_eq!(updated, fabric.replace("1.0.0", "1.1.0+mc1.20.1"));
+
+    let (quilt, updated) = test_changer::<FabricModJson>("quilt.mod.json", None).await;
+    assert_eq!(updated, quilt.replace("1.0.0", "1.1.0+mc1.20.1"));
+}
+
+#[tokio::test]
+async fn mods_toml() {
+    let (source, updated) = test_changer::<ModsToml>("mods.toml", Some("example")).await;
+    assert_eq!(updated, source.replace("\"1.0.0\"", "\"1.1.0+mc1.20.1\""));
+}
+
+#[tokio::test]
+#[should_panic(expected = "placeholder")]
+async fn placeholder() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mods.toml");
+    std::fs::write(
+        &path,
+        "[[mods]]\nmodId=\"example\"\nversion=\"${file.jarVersion}\"\n",
+    )
+    .unwrap();
+    ModsToml::parse(None, path.to_str())
+        .set_version("1.0.0")
+        .await;
+}
+
+#[tokio::test]
+async fn plugin_yml() {
+    let (source, updated) = test_changer::<PluginYml>("plugin.yml", None).await;
+    assert_eq!(updated, source.replace("'1.0.0'", "'1.1.0+mc1.20.1'"));
+}
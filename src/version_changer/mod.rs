@@ -1,7 +1,31 @@
+mod android;
+mod apple;
+mod changer_string;
+mod cmake;
 mod command;
+mod debian_changelog;
+mod dotnet;
+mod env_file;
+mod exec;
+mod git_tag;
+mod github_output;
+mod gradle_build_script;
 mod gradle_properties;
+mod gradle_version_catalog;
+mod helm_chart;
+mod jar_manifest;
+mod json_pointer;
+mod maven_pom;
+mod meson;
+mod minecraft_mod;
 mod npm_package_json;
+mod pyproject;
 mod regex_pattern;
+mod rpm_spec;
+mod toml_path;
+mod unity_project_settings;
+mod version_file;
+mod yaml_path;
 mod cargo;
 
 use serde::de::value::SeqAccessDeserializer;
@@ -11,20 +35,36 @@ use std::fmt::{Debug, Display};
 use std::future::Future;
 use std::pin::Pin;
 
+pub(crate) use changer_string::changer_string;
 pub(crate) use command::VersionChangerCommand;
 
 pub(crate) trait VersionChanger: Display + Debug {
+    /// false for sinks. `load_version` is never called if false
+    const READABLE: bool = true;
+    /// false for sources. `set_version` is never called if false
+    const WRITABLE: bool = true;
+
     fn parse(info: Option<&str>, path: Option<&str>) -> Self;
     async fn load_version(&self) -> String;
     async fn set_version(&self, version: &str);
 }
 
 pub(crate) trait DynVersionChanger: Display + Debug {
+    fn readable(&self) -> bool;
+    fn writable(&self) -> bool;
     fn load_version(&self) -> Pin<Box<dyn Future<Output = String> + '_>>;
     fn set_version<'a>(&'a self, version: &'a str) -> Pin<Box<dyn Future<Output = ()> + 'a>>;
 }
 
 impl<T: VersionChanger> DynVersionChanger for T {
+    fn readable(&self) -> bool {
+        T::READABLE
+    }
+
+    fn writable(&self) -> bool {
+        T::WRITABLE
+    }
+
     fn load_version(&self) -> Pin<Box<dyn Future<Output = String> + '_>> {
         Box::pin(self.load_version())
     }
@@ -41,8 +81,11 @@ pub(crate) struct VersionChangers {
 
 impl VersionChangers {
     pub async fn get_version(&self) -> String {
+        if self.changers.is_empty() {
+            panic!("no version changers");
+        }
         let mut version = None;
-        for changer in &self.changers {
+        for changer in self.changers.iter().filter(|x| x.readable()) {
             let new_version = changer.load_version().await;
             if let Some(old_version) = &version {
                 if old_version != &new_version {
@@ -52,11 +95,11 @@ impl VersionChangers {
                 version = Some(new_version);
             }
         }
-        version.expect("no version changers")
+        version.expect("no readable version changers")
     }
 
     pub async fn set_version(&self, version: String) {
-        for changer in &self.changers {
+        for changer in self.changers.iter().filter(|x| x.writable()) {
             changer.set_version(&version).await;
         }
     }
@@ -117,15 +160,68 @@ impl<'de> Deserialize<'de> for Box<dyn DynVersionChanger> {
         #[derive(Deserialize)]
         #[serde(tag = "type")]
         enum KnownChanger {
+            #[serde(rename = "android")]
+            Android(android::Android),
+            #[serde(rename = "apple")]
+            Apple(apple::Apple),
             #[serde(rename = "npm-package-json")]
             #[serde(alias = "npm")]
             NpmPackageJson(npm_package_json::NpmPackageJson),
+            #[serde(rename = "cmake")]
+            Cmake(cmake::Cmake),
+            #[serde(rename = "debian-changelog")]
+            DebianChangelog(debian_changelog::DebianChangelog),
+            #[serde(rename = "dotnet")]
+            Dotnet(dotnet::Dotnet),
+            #[serde(rename = "fabric-mod-json")]
+            #[serde(alias = "quilt-mod-json")]
+            FabricModJson(minecraft_mod::FabricModJson),
+            #[serde(rename = "env-file")]
+            EnvFile(env_file::EnvFile),
+            #[serde(rename = "exec")]
+            Exec(exec::Exec),
+            #[serde(rename = "git-tag")]
+            GitTag(git_tag::GitTag),
+            #[serde(rename = "github-output")]
+            GithubOutput(github_output::GithubOutput),
+            #[serde(rename = "gradle-build-script")]
+            GradleBuildScript(gradle_build_script::GradleBuildScript),
             #[serde(rename = "gradle-properties")]
             GradleProperties(gradle_properties::GradleProperties),
+            #[serde(rename = "gradle-version-catalog")]
+            GradleVersionCatalog(gradle_version_catalog::GradleVersionCatalog),
+            #[serde(rename = "helm-chart")]
+            HelmChart(helm_chart::HelmChart),
+            #[serde(rename = "meson")]
+            Meson(meson::Meson),
+            #[serde(rename = "mods-toml")]
+            #[serde(alias = "neoforge-mods-toml")]
+            ModsToml(minecraft_mod::ModsToml),
+            #[serde(rename = "plugin-yml")]
+            #[serde(alias = "paper-plugin-yml")]
+            PluginYml(minecraft_mod::PluginYml),
+            #[serde(rename = "pyproject")]
+            Pyproject(pyproject::Pyproject),
             #[serde(rename = "regex-pattern")]
             RegexPattern(regex_pattern::RegexPattern),
+            #[serde(rename = "rpm-spec")]
+            RpmSpec(rpm_spec::RpmSpec),
+            #[serde(rename = "toml-path")]
+            TomlPath(toml_path::TomlPath),
+            #[serde(rename = "unity-project-settings")]
+            UnityProjectSettings(unity_project_settings::UnityProjectSettings),
+            #[serde(rename = "version-file")]
+            VersionFile(version_file::VersionFile),
+            #[serde(rename = "yaml-path")]
+            YamlPath(yaml_path::YamlPath),
             #[serde(rename = "cargo")]
             Cargo(cargo::Cargo),
+            #[serde(rename = "jar-manifest")]
+            JarManifest(jar_manifest::JarManifest),
+            #[serde(rename = "json-pointer")]
+            JsonPointer(json_pointer::JsonPointer),
+            #[serde(rename = "maven-pom")]
+            MavenPom(maven_pom::MavenPom),
         }
 
         let repr: Reprs = Deserialize::deserialize(deserializer)?;
@@ -135,49 +231,150 @@ impl<'de> Deserialize<'de> for Box<dyn DynVersionChanger> {
             Tuple1((kind,)) => create_single_changer(&kind, None, None),
             Tuple2((kind, info)) => create_single_changer(&kind, Some(&info), None),
             Tuple3((kind, info, path)) => create_single_changer(&kind, Some(&info), Some(&path)),
+            AsStruct(Android(changer)) => Box::new(changer),
+            AsStruct(Apple(changer)) => Box::new(changer),
             AsStruct(NpmPackageJson(changer)) => Box::new(changer),
+            AsStruct(Cmake(changer)) => Box::new(changer),
+            AsStruct(DebianChangelog(changer)) => Box::new(changer),
+            AsStruct(Dotnet(changer)) => Box::new(changer),
+            AsStruct(FabricModJson(changer)) => Box::new(changer),
+            AsStruct(EnvFile(changer)) => Box::new(changer),
+            AsStruct(Exec(changer)) => Box::new(changer),
+            AsStruct(GitTag(changer)) => Box::new(changer),
+            AsStruct(GithubOutput(changer)) => Box::new(changer),
+            AsStruct(GradleBuildScript(changer)) => Box::new(changer),
             AsStruct(GradleProperties(changer)) => Box::new(changer),
+            AsStruct(GradleVersionCatalog(changer)) => Box::new(changer),
+            AsStruct(HelmChart(changer)) => Box::new(changer),
+            AsStruct(Meson(changer)) => Box::new(changer),
+            AsStruct(ModsToml(changer)) => Box::new(changer),
+            AsStruct(PluginYml(changer)) => Box::new(changer),
+            AsStruct(Pyproject(changer)) => Box::new(changer),
             AsStruct(RegexPattern(changer)) => Box::new(changer),
+            AsStruct(RpmSpec(changer)) => Box::new(changer),
+            AsStruct(TomlPath(changer)) => Box::new(changer),
+            AsStruct(UnityProjectSettings(changer)) => Box::new(changer),
+            AsStruct(VersionFile(changer)) => Box::new(changer),
+            AsStruct(YamlPath(changer)) => Box::new(changer),
             AsStruct(Cargo(changer)) => Box::new(changer),
+            AsStruct(JarManifest(changer)) => Box::new(changer),
+            AsStruct(JsonPointer(changer)) => Box::new(changer),
+            AsStruct(MavenPom(changer)) => Box::new(changer),
         })
     }
 }
 
 pub(crate) fn parse_version_changers(parse: &str) -> VersionChangers {
     VersionChangers {
-        changers: parse.split(';').map(parse_single_changer).collect(),
+        changers: changer_string::parse_changers(parse)
+            .into_iter()
+            .map(create_changer_from_spec)
+            .collect(),
     }
 }
 
 fn parse_single_changer(parse: &str) -> Box<dyn DynVersionChanger> {
-    let (kind, info, path) = if let Some((kind, rest)) = parse.split_once(':') {
-        if let Some((info, path)) = rest.split_once('@') {
-            (kind, info, path)
-        } else {
-            (kind, rest, "")
-        }
-    } else if let Some((kind, path)) = parse.split_once('@') {
-        (kind, "", path)
-    } else {
-        (parse, "", "")
-    };
-
-    let info = if info.is_empty() { None } else { Some(info) };
-    let path = if path.is_empty() { None } else { Some(path) };
+    let mut specs = changer_string::parse_changers(parse);
+    if specs.len() != 1 {
+        panic!("expected single version changer: {}", parse);
+    }
+    create_changer_from_spec(specs.pop().unwrap())
+}
 
-    create_single_changer(kind, info, path)
+fn create_changer_from_spec(spec: changer_string::ChangerSpec) -> Box<dyn DynVersionChanger> {
+    create_single_changer(&spec.kind, spec.info.as_deref(), spec.path.as_deref())
 }
 
-fn create_single_changer(
+pub(crate) fn create_single_changer(
     kind: &str,
     info: Option<&str>,
     path: Option<&str>,
 ) -> Box<dyn DynVersionChanger> {
     match kind {
+        "android" => Box::new(android::Android::parse(info, path)),
+        "apple" => Box::new(apple::Apple::parse(info, path)),
         "npm" | "npm-package-json" => Box::new(npm_package_json::NpmPackageJson::parse(info, path)),
+        "cmake" => Box::new(cmake::Cmake::parse(info, path)),
+        "debian-changelog" => Box::new(debian_changelog::DebianChangelog::parse(info, path)),
+        "dotnet" => Box::new(dotnet::Dotnet::parse(info, path)),
+        "fabric-mod-json" | "quilt-mod-json" => {
+            Box::new(minecraft_mod::FabricModJson::parse(info, path))
+        }
+        "env-file" => Box::new(env_file::EnvFile::parse(info, path)),
+        "exec" => Box::new(exec::Exec::parse(info, path)),
+        "git-tag" => Box::new(git_tag::GitTag::parse(info, path)),
+        "github-output" => Box::new(github_output::GithubOutput::parse(info, path)),
+        "gradle-build-script" => {
+            Box::new(gradle_build_script::GradleBuildScript::parse(info, path))
+        }
         "gradle-properties" => Box::new(gradle_properties::GradleProperties::parse(info, path)),
+        "gradle-version-catalog" => {
+            Box::new(gradle_version_catalog::GradleVersionCatalog::parse(info, path))
+        }
+        "helm-chart" => Box::new(helm_chart::HelmChart::parse(info, path)),
+        "meson" => Box::new(meson::Meson::parse(info, path)),
+        "mods-toml" | "neoforge-mods-toml" => {
+            Box::new(minecraft_mod::ModsToml::parse(info, path))
+        }
+        "plugin-yml" | "paper-plugin-yml" => {
+            Box::new(minecraft_mod::PluginYml::parse(info, path))
+        }
+        "pyproject" => Box::new(pyproject::Pyproject::parse(info, path)),
         "regex-pattern" => Box::new(regex_pattern::RegexPattern::parse(info, path)),
+        "rpm-spec" => Box::new(rpm_spec::RpmSpec::parse(info, path)),
+        "toml-path" => Box::new(toml_path::TomlPath::parse(info, path)),
+        "unity-project-settings" => {
+            Box::new(unity_project_settings::UnityProjectSettings::parse(info, path))
+        }
+        "version-file" => Box::new(version_file::VersionFile::parse(info, path)),
+        "yaml-path" => Box::new(yaml_path::YamlPath::parse(info, path)),
         "cargo" => Box::new(cargo::Cargo::parse(info, path)),
+        "jar-manifest" => Box::new(jar_manifest::JarManifest::parse(info, path)),
+        "json-pointer" => Box::new(json_pointer::JsonPointer::parse(info, path)),
+        "maven-pom" => Box::new(maven_pom::MavenPom::parse(info, path)),
         unknown => panic!("unknown version changer kind: {}", unknown),
     }
 }
+
+/// Copies the fixture in `__tests__resources/version-changer` to a temporary directory
+#[cfg(test)]
+fn copy_fixture(name: &str) -> tempfile::TempDir {
+    fn copy_dir(from: &std::path::Path, to: &std::path::Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let to = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &to);
+            } else {
+                std::fs::copy(entry.path(), to).unwrap();
+            }
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    copy_dir(
+        &std::path::Path::new("__tests__resources/version-changer").join(name),
+        dir.path(),
+    );
+    dir
+}
+
+#[tokio::test]
+async fn readable_and_writable() {
+    let dir = tempfile::tempdir().unwrap();
+    let version_file = dir.path().join("VERSION");
+    let env_file = dir.path().join(".release.env");
+    std::fs::write(&version_file, "1.0.0\n").unwrap();
+
+    let changers = parse_version_changers(&format!(
+        "version-file@{};env-file:{}",
+        version_file.display(),
+        env_file.display()
+    ));
+    // env-file is not readable
+    assert_eq!(changers.get_version().await, "1.0.0");
+    changers.set_version("1.1.0".to_owned()).await;
+    assert_eq!(std::fs::read_to_string(&version_file).unwrap(), "1.1.0\n");
+    assert_eq!(std::fs::read_to_string(&env_file).unwrap(), "VERSION=1.1.0\n");
+}
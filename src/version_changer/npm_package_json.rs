@@ -1,13 +1,21 @@
 use crate::utils;
+use crate::utils::json::{JsonValue, Token};
 use crate::version_changer::VersionChanger;
+use cargo_metadata::semver;
+use log::debug;
 use serde::Deserialize;
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct NpmPackageJson {
     #[serde(default = "path_default")]
     path: PathBuf,
+    /// the name of the workspace member to change, or `*` for all members.
+    /// if not specified, the package.json at the path is changed.
+    #[serde(alias = "info")]
+    package: Option<String>,
 }
 
 fn path_default() -> PathBuf {
@@ -16,47 +24,452 @@ fn path_default() -> PathBuf {
 
 impl Display for NpmPackageJson {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "npm(at {})", self.path.display())
+        match &self.package {
+            Some(package) => write!(f, "npm(at {}, package {})", self.path.display(), package),
+            None => write!(f, "npm(at {})", self.path.display()),
+        }
     }
 }
 
+/// lockfiles which have the version of the package
+const LOCKFILES: &[&str] = &["package-lock.json", "npm-shrinkwrap.json"];
+
+/// tables whose ranges for workspace members are updated
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "devDependencies", "peerDependencies"];
+
+/// A package in the workspace
+#[derive(Debug)]
+struct Package {
+    /// the path relative to the workspace root separated with `/`. empty for the root package
+    location: String,
+    name: Option<String>,
+    version: Option<String>,
+}
+
+impl NpmPackageJson {
+    fn root_dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Returns the root package and workspace members
+    async fn packages(&self) -> Vec<Package> {
+        let root = read_json(&self.path).await;
+        let patterns = match root.get("workspaces") {
+            Some(serde_json::Value::Array(patterns)) => patterns,
+            // yarn style `{ "packages": [...] }`
+            Some(workspaces) => workspaces
+                .get("packages")
+                .and_then(|x| x.as_array())
+                .expect("workspaces in package.json is not an array"),
+            None => panic!("no workspaces in package.json"),
+        };
+        let patterns = patterns
+            .iter()
+            .map(|x| x.as_str().expect("workspace pattern is not a string"))
+            .collect::<Vec<_>>();
+
+        let mut locations = patterns
+            .iter()
+            .filter(|pattern| !pattern.starts_with('!'))
+            .flat_map(|pattern| utils::glob::expand(self.root_dir(), pattern))
+            .filter(|location| !location.split('/').any(|x| x == "node_modules"))
+            .filter(|location| {
+                !patterns
+                    .iter()
+                    .filter_map(|x| x.strip_prefix('!'))
+                    .any(|negated| utils::glob::matches(negated, location))
+            })
+            .filter(|location| {
+                self.root_dir()
+                    .join(location)
+                    .join("package.json")
+                    .is_file()
+            })
+            .collect::<Vec<_>>();
+        locations.sort();
+        locations.dedup();
+
+        let mut packages = vec![package(String::new(), &root)];
+        for location in locations {
+            let json = read_json(&self.root_dir().join(&location).join("package.json")).await;
+            packages.push(package(location, &json));
+        }
+        packages
+    }
+}
+
+fn package(location: String, json: &serde_json::Value) -> Package {
+    let string = |key: &str| json.get(key).and_then(|x| x.as_str()).map(Into::into);
+    Package {
+        location,
+        name: string("name"),
+        version: string("version"),
+    }
+}
+
+async fn read_json(path: &Path) -> serde_json::Value {
+    let read = tokio::fs::read_to_string(path)
+        .await
+        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+    serde_json::from_str(&read).unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e))
+}
+
 impl VersionChanger for NpmPackageJson {
     fn parse(info: Option<&str>, path: Option<&str>) -> Self {
-        if info.is_some() {
-            panic!("invalid npm package.json version changer");
-        }
         Self {
             path: path.map(Into::into).unwrap_or_else(path_default),
+            package: info.map(Into::into),
         }
     }
 
     async fn load_version(&self) -> String {
-        let reader = &tokio::fs::read_to_string(&self.path)
-            .await
-            .expect("reading package.json");
-        serde_json::from_str::<serde_json::Value>(reader)
-            .expect("parsing package.json")
-            .get("version")
-            .expect("getting version from package.json")
-            .as_str()
-            .expect("version in package.json is not a string")
-            .to_string()
+        let Some(selector) = &self.package else {
+            return read_json(&self.path)
+                .await
+                .get("version")
+                .expect("getting version from package.json")
+                .as_str()
+                .expect("version in package.json is not a string")
+                .to_string();
+        };
+
+        let packages = self.packages().await;
+        let mut version = None::<&str>;
+        for package in select(&packages, selector) {
+            let name = package.name.as_deref().unwrap();
+            let package_version = package
+                .version
+                .as_deref()
+                .unwrap_or_else(|| panic!("no version in package {name}"));
+            match version {
+                None => version = Some(package_version),
+                Some(version) if version == package_version => {}
+                Some(version) => panic!(
+                    "version mismatch in workspace: {} != {} of {}",
+                    version, package_version, name
+                ),
+            }
+        }
+        version.unwrap().to_owned()
     }
 
     async fn set_version(&self, version: &str) {
-        let read = &tokio::fs::read_to_string(&self.path)
-            .await
-            .expect("reading package.json");
-        let mut parsed = utils::json::parse_json(read).expect("parsing package.json");
-        let as_object = parsed
-            .value
-            .as_object_mut()
-            .expect("package.json is not an object");
-        let quoted = utils::json::quote_string(version);
-        as_object.set(r#""version""#, utils::json::Token::StringLiteral(&quoted));
-        let created = parsed.to_string();
-        tokio::fs::write(&self.path, created)
-            .await
-            .expect("writing package.json");
+        let Some(selector) = &self.package else {
+            update_package_json(&self.path, true, version, &HashSet::new()).await;
+            let root = vec![String::new()];
+            for lockfile in LOCKFILES {
+                update_lockfile(
+                    &self.root_dir().join(lockfile),
+                    version,
+                    &root,
+                    &[],
+                    &HashSet::new(),
+                )
+                .await;
+            }
+            return;
+        };
+
+        let packages = self.packages().await;
+        let targets = select(&packages, selector);
+        let locations = targets
+            .iter()
+            .map(|x| x.location.clone())
+            .collect::<Vec<_>>();
+        let names = targets
+            .iter()
+            .map(|x| x.name.clone().unwrap())
+            .collect::<HashSet<_>>();
+
+        for package in &packages {
+            let path = self.root_dir().join(&package.location).join("package.json");
+            update_package_json(
+                &path,
+                locations.contains(&package.location),
+                version,
+                &names,
+            )
+            .await;
+        }
+
+        let all_locations = packages.into_iter().map(|x| x.location).collect::<Vec<_>>();
+        for lockfile in LOCKFILES {
+            update_lockfile(
+                &self.root_dir().join(lockfile),
+                version,
+                &locations,
+                &all_locations,
+                &names,
+            )
+            .await;
+        }
+    }
+}
+
+/// Selects workspace members with the name, or all members with `*`
+fn select<'a>(packages: &'a [Package], selector: &str) -> Vec<&'a Package> {
+    let members = packages
+        .iter()
+        .filter(|x| !x.location.is_empty() && x.name.is_some());
+    let selected = if selector == "*" {
+        members.filter(|x| x.version.is_some()).collect::<Vec<_>>()
+    } else {
+        members
+            .filter(|x| x.name.as_deref() == Some(selector))
+            .collect::<Vec<_>>()
+    };
+    if selected.is_empty() {
+        panic!("no workspace member matching {selector} found");
+    }
+    selected
+}
+
+/// Computes the new range for the workspace member with keeping the range operator.
+/// Returns None if the range should not be changed.
+fn update_range(range: &str, version: &str) -> Option<String> {
+    let (protocol, rest) = match range.strip_prefix("workspace:") {
+        Some(rest) => ("workspace:", rest),
+        None => ("", range),
+    };
+    let operator_len = rest.len() - rest.trim_start_matches(['^', '~', '>', '<', '=']).len();
+    let (operator, old) = rest.split_at(operator_len);
+    if semver::Version::parse(old).is_err() {
+        // `workspace:*`, `workspace:^`, or complex ranges
+        debug!("keeping range {range}");
+        return None;
+    }
+    let new = format!("{protocol}{operator}{version}");
+    (new != range).then_some(new)
+}
+
+/// Collects changes for a package object of package.json or `packages` in lockfile
+fn package_changes(
+    json: &serde_json::Value,
+    prefix: &[&str],
+    set_version: bool,
+    version: &str,
+    names: &HashSet<String>,
+) -> Vec<(Vec<String>, String)> {
+    let path = |keys: &[&str]| {
+        prefix
+            .iter()
+            .chain(keys)
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut changes = Vec::new();
+    if set_version && json.get("version").is_some() {
+        changes.push((path(&["version"]), version.to_owned()));
+    }
+    for table in DEPENDENCY_TABLES {
+        let Some(dependencies) = json.get(table).and_then(|x| x.as_object()) else {
+            continue;
+        };
+        for (name, range) in dependencies {
+            if !names.contains(name) {
+                continue;
+            }
+            if let Some(new_range) = range.as_str().and_then(|x| update_range(x, version)) {
+                changes.push((path(&[table, name]), new_range));
+            }
+        }
+    }
+    changes
+}
+
+/// Applies changes to the json file with preserving the format
+async fn apply_changes(path: &Path, source: &str, changes: Vec<(Vec<String>, String)>) {
+    if changes.is_empty() {
+        return;
+    }
+    let changes = changes
+        .into_iter()
+        .map(|(keys, value)| {
+            let keys = keys
+                .iter()
+                .map(|x| utils::json::quote_string(x))
+                .collect::<Vec<_>>();
+            (keys, utils::json::quote_string(&value))
+        })
+        .collect::<Vec<_>>();
+
+    let mut parsed = utils::json::parse_json(source)
+        .unwrap_or_else(|e| panic!("parsing {}: {:?}", path.display(), e));
+    for (keys, quoted) in &changes {
+        set_string(&mut parsed.value, keys, quoted);
+    }
+    tokio::fs::write(path, parsed.to_string())
+        .await
+        .unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+}
+
+fn set_string<'src>(value: &mut JsonValue<'src>, keys: &[String], quoted: &'src str) {
+    let (last, parents) = keys.split_last().unwrap();
+    let object = parents
+        .iter()
+        .try_fold(value, |value, key| value.as_object_mut()?.get_mut(key))
+        .and_then(|x| x.as_object_mut())
+        .unwrap_or_else(|| panic!("{} is not an object", parents.join(".")));
+    object.set(last, Token::StringLiteral(quoted));
+}
+
+async fn update_package_json(
+    path: &Path,
+    set_version: bool,
+    version: &str,
+    names: &HashSet<String>,
+) {
+    let read = tokio::fs::read_to_string(path)
+        .await
+        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+    let json = serde_json::from_str::<serde_json::Value>(&read)
+        .unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e));
+    if set_version && json.get("version").is_none() {
+        panic!("no version in {}", path.display());
+    }
+    let changes = package_changes(&json, &[], set_version, version, names);
+    apply_changes(path, &read, changes).await;
+}
+
+/// Updates the lockfile if exists.
+///
+/// `version` (lockfile v1, v2) and `packages[location].version` (v2, v3) of `locations` are updated,
+/// and ranges for `names` in `packages[location]` of `dependents` are updated.
+async fn update_lockfile(
+    path: &Path,
+    version: &str,
+    locations: &[String],
+    dependents: &[String],
+    names: &HashSet<String>,
+) {
+    let read = match tokio::fs::read_to_string(path).await {
+        Ok(read) => read,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => panic!("reading {}: {}", path.display(), e),
+    };
+    let json = serde_json::from_str::<serde_json::Value>(&read)
+        .unwrap_or_else(|e| panic!("parsing {}: {}", path.display(), e));
+
+    let mut changes = Vec::new();
+    if locations.iter().any(String::is_empty) && json.get("version").is_some() {
+        changes.push((vec!["version".to_owned()], version.to_owned()));
     }
+    if let Some(packages) = json.get("packages").and_then(|x| x.as_object()) {
+        for location in locations.iter().chain(dependents) {
+            if let Some(package) = packages.get(location) {
+                let set_version = locations.contains(location);
+                let prefix = ["packages", location.as_str()];
+                changes.extend(package_changes(
+                    package,
+                    &prefix,
+                    set_version,
+                    version,
+                    names,
+                ));
+            }
+        }
+    }
+    changes.dedup();
+    apply_changes(path, &read, changes).await;
+}
+
+#[test]
+fn update_range_test() {
+    assert_eq!(update_range("^1.0.0", "1.1.0").as_deref(), Some("^1.1.0"));
+    assert_eq!(update_range("~1.0.0", "1.1.0").as_deref(), Some("~1.1.0"));
+    assert_eq!(update_range("1.0.0", "1.1.0").as_deref(), Some("1.1.0"));
+    assert_eq!(update_range(">=1.0.0", "1.1.0").as_deref(), Some(">=1.1.0"));
+    assert_eq!(
+        update_range("workspace:^1.0.0", "1.1.0").as_deref(),
+        Some("workspace:^1.1.0")
+    );
+    assert_eq!(update_range("workspace:*", "1.1.0"), None);
+    assert_eq!(update_range("workspace:^", "1.1.0"), None);
+    assert_eq!(update_range("^1.0.0 || ^2.0.0", "1.1.0"), None);
+    assert_eq!(update_range("^1.1.0", "1.1.0"), None);
+}
+
+#[tokio::test]
+async fn lockfiles() {
+    let dir = tempfile::tempdir().unwrap();
+    let package_json = "{\n  \"name\": \"example\",\n  \"version\": \"1.0.0\"\n}\n";
+    // lockfile v3
+    let package_lock = r#"{
+  "name": "example",
+  "version": "1.0.0",
+  "lockfileVersion": 3,
+  "requires": true,
+  "packages": {
+    "": {
+      "name": "example",
+      "version": "1.0.0",
+      "dependencies": {
+        "left-pad": "^1.0.0"
+      }
+    },
+    "node_modules/left-pad": {
+      "version": "1.0.0"
+    }
+  }
+}
+"#;
+    // lockfile v1
+    let shrinkwrap = r#"{
+  "name": "example",
+  "version": "1.0.0",
+  "lockfileVersion": 1,
+  "dependencies": {
+    "left-pad": {
+      "version": "1.0.0"
+    }
+  }
+}
+"#;
+    std::fs::write(dir.path().join("package.json"), package_json).unwrap();
+    std::fs::write(dir.path().join("package-lock.json"), package_lock).unwrap();
+    std::fs::write(dir.path().join("npm-shrinkwrap.json"), shrinkwrap).unwrap();
+
+    let changer = NpmPackageJson::parse(None, dir.path().join("package.json").to_str());
+    changer.set_version("1.1.0").await;
+    assert_eq!(changer.load_version().await, "1.1.0");
+
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("package-lock.json")).unwrap(),
+        package_lock.replacen("\"1.0.0\"", "\"1.1.0\"", 2)
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("npm-shrinkwrap.json")).unwrap(),
+        shrinkwrap.replacen("\"1.0.0\"", "\"1.1.0\"", 1)
+    );
+}
+
+#[tokio::test]
+async fn workspaces() {
+    let dir = super::copy_fixture("npm-workspaces");
+    let root = dir.path().join("package.json");
+    let read = |path: &str| std::fs::read_to_string(dir.path().join(path)).unwrap();
+
+    let core = NpmPackageJson::parse(Some("@example/core"), root.to_str());
+    assert_eq!(core.load_version().await, "1.0.0");
+    core.set_version("1.1.0").await;
+    assert_eq!(core.load_version().await, "1.1.0");
+
+    assert!(read("packages/core/package.json").contains(r#""version": "1.1.0""#));
+    let cli = read("packages/cli/package.json");
+    assert!(cli.contains(r#""version": "1.0.0""#));
+    assert!(cli.contains(r#""@example/core": "^1.1.0""#));
+    assert!(cli.contains(r#""@example/utils": "workspace:*""#));
+    let lock = read("package-lock.json");
+    assert!(lock.contains(
+        "\"packages/core\": {\n      \"name\": \"@example/core\",\n      \"version\": \"1.1.0\""
+    ));
+    assert!(lock.contains(r#""@example/core": "^1.1.0""#));
+
+    let all = NpmPackageJson::parse(Some("*"), root.to_str());
+    all.set_version("2.0.0").await;
+    assert_eq!(all.load_version().await, "2.0.0");
+    assert!(read("package.json").contains(r#""version": "0.0.0""#));
+    assert!(read("packages/cli/package.json").contains(r#""@example/core": "^2.0.0""#));
+    // excluded by the negated pattern
+    assert!(read("packages/internal/package.json").contains(r#""version": "0.1.0""#));
 }
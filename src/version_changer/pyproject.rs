@@ -0,0 +1,259 @@
+use crate::utils;
+use crate::version_changer::VersionChanger;
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Pyproject {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("pyproject.toml")
+}
+
+impl Display for Pyproject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "pyproject(at {})", self.path.display())
+    }
+}
+
+/// The place the version is declared
+enum VersionLocation {
+    /// `[project].version` of PEP 621
+    Project,
+    /// `[tool.poetry].version`
+    Poetry,
+    /// `__version__ = "..."` in python module for dynamic version
+    Module { path: PathBuf, variable: String },
+}
+
+impl Pyproject {
+    async fn read(&self) -> DocumentMut {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .expect("reading pyproject.toml")
+            .parse()
+            .expect("parsing pyproject.toml")
+    }
+
+    async fn version_location(&self, document: &DocumentMut) -> VersionLocation {
+        let project = document.get("project");
+
+        if project.and_then(|x| x.get("version")).is_some() {
+            return VersionLocation::Project;
+        }
+
+        let dynamic = project
+            .and_then(|x| x.get("dynamic"))
+            .and_then(|x| x.as_array())
+            .is_some_and(|x| x.iter().any(|x| x.as_str() == Some("version")));
+
+        if dynamic {
+            let base = self.path.parent().unwrap_or(Path::new(""));
+            let tool = document.get("tool");
+            let setuptools_attr = tool
+                .and_then(|x| x.get("setuptools"))
+                .and_then(|x| x.get("dynamic"))
+                .and_then(|x| x.get("version"))
+                .and_then(|x| x.get("attr"))
+                .and_then(|x| x.as_str());
+            let hatch_path = tool
+                .and_then(|x| x.get("hatch"))
+                .and_then(|x| x.get("version"))
+                .and_then(|x| x.get("path"))
+                .and_then(|x| x.as_str());
+
+            if let Some(attr) = setuptools_attr {
+                let (module, variable) = attr
+                    .rsplit_once('.')
+                    .unwrap_or_else(|| panic!("invalid version attr: {attr}"));
+                return VersionLocation::Module {
+                    path: find_module(base, module).await,
+                    variable: variable.to_owned(),
+                };
+            } else if let Some(path) = hatch_path {
+                return VersionLocation::Module {
+                    path: base.join(path),
+                    variable: "__version__".to_owned(),
+                };
+            } else if !has_poetry_version(document) {
+                panic!("dynamic version is only supported with tool.setuptools.dynamic.version.attr, tool.hatch.version.path or tool.poetry.version");
+            }
+        }
+
+        // poetry 2 declares `dynamic = ["version"]` and keeps the version in tool.poetry
+        if has_poetry_version(document) {
+            return VersionLocation::Poetry;
+        }
+
+        panic!("no version found in pyproject.toml");
+    }
+}
+
+fn has_poetry_version(document: &DocumentMut) -> bool {
+    document
+        .get("tool")
+        .and_then(|x| x.get("poetry"))
+        .and_then(|x| x.get("version"))
+        .is_some()
+}
+
+/// Finds the source file of the module from the project root or `src` directory
+async fn find_module(base: &Path, module: &str) -> PathBuf {
+    let exists = |path: PathBuf| async move {
+        tokio::fs::try_exists(&path)
+            .await
+            .unwrap_or_else(|e| panic!("checking {}: {}", path.display(), e))
+            .then_some(path)
+    };
+    let relative = module.replace('.', "/");
+    for root in [base.to_owned(), base.join("src")] {
+        if let Some(package) = exists(root.join(&relative).join("__init__.py")).await {
+            return package;
+        }
+        if let Some(module) = exists(root.join(format!("{relative}.py"))).await {
+            return module;
+        }
+    }
+    panic!("module {module} for version attr not found");
+}
+
+fn table_version<'a>(
+    document: &'a mut DocumentMut,
+    location: &VersionLocation,
+) -> &'a mut toml_edit::Value {
+    let item = match location {
+        VersionLocation::Project => &mut document["project"]["version"],
+        VersionLocation::Poetry => &mut document["tool"]["poetry"]["version"],
+        VersionLocation::Module { .. } => unreachable!(),
+    };
+    item.as_value_mut()
+        .filter(|x| x.is_str())
+        .expect("version in pyproject.toml is not a string")
+}
+
+/// Finds the range of the string content assigned to the variable
+fn find_assignment(source: &str, variable: &str) -> Range<usize> {
+    let pattern = Regex::new(&format!(
+        r#"(?m)^{}\s*(?::[^=\n]*)?=\s*(?:"(?<double>[^"\\\n]*)"|'(?<single>[^'\\\n]*)')"#,
+        regex::escape(variable)
+    ))
+    .unwrap();
+    let captures = pattern
+        .captures(source)
+        .unwrap_or_else(|| panic!("no {variable} = \"...\" found in the module"));
+    captures
+        .name("double")
+        .or_else(|| captures.name("single"))
+        .unwrap()
+        .range()
+}
+
+impl VersionChanger for Pyproject {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        if info.is_some() {
+            panic!("invalid pyproject version changer");
+        }
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let mut document = self.read().await;
+        match self.version_location(&document).await {
+            VersionLocation::Module { path, variable } => {
+                let source = tokio::fs::read_to_string(&path)
+                    .await
+                    .expect("reading version module");
+                source[find_assignment(&source, &variable)].to_owned()
+            }
+            location => table_version(&mut document, &location)
+                .as_str()
+                .unwrap()
+                .to_owned(),
+        }
+    }
+
+    async fn set_version(&self, version: &str) {
+        let mut document = self.read().await;
+        match self.version_location(&document).await {
+            VersionLocation::Module { path, variable } => {
+                if version.contains(['"', '\'', '\\', '\n']) {
+                    panic!("version for python module cannot contain quotes or backslash");
+                }
+                let mut source = tokio::fs::read_to_string(&path)
+                    .await
+                    .expect("reading version module");
+                let range = find_assignment(&source, &variable);
+                source.replace_range(range, version);
+                tokio::fs::write(&path, source)
+                    .await
+                    .expect("writing version module");
+            }
+            location => {
+                utils::toml::set_string(table_version(&mut document, &location), version);
+                tokio::fs::write(&self.path, document.to_string())
+                    .await
+                    .expect("writing pyproject.toml");
+            }
+        }
+    }
+}
+
+/// Sets the version of the fixture in `pyproject` directory from 1.0.0 to 1.1.0.dev0
+#[cfg(test)]
+async fn test_pyproject(name: &str) -> tempfile::TempDir {
+    let dir = super::copy_fixture(&format!("pyproject/{name}"));
+    let changer = Pyproject::parse(
+        None,
+        Some(dir.path().join("pyproject.toml").to_str().unwrap()),
+    );
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0.dev0").await;
+    assert_eq!(changer.load_version().await, "1.1.0.dev0");
+    dir
+}
+
+#[tokio::test]
+async fn pep621_and_poetry() {
+    let dir = test_pyproject("pep621").await;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("pyproject.toml")).unwrap(),
+        "[project]\nname = \"example\"\nversion = \"1.1.0.dev0\" # version\n"
+    );
+
+    let dir = test_pyproject("poetry").await;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("pyproject.toml")).unwrap(),
+        "[tool.poetry]\nname = \"example\"\nversion = '1.1.0.dev0'\n"
+    );
+
+    // poetry 2
+    let dir = test_pyproject("poetry2").await;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("pyproject.toml")).unwrap(),
+        "[project]\nname = \"example\"\ndynamic = [\"version\"]\n\n[tool.poetry]\nversion = \"1.1.0.dev0\"\n"
+    );
+}
+
+#[tokio::test]
+async fn dynamic_version() {
+    let dir = test_pyproject("setuptools-attr").await;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("src/example/about.py")).unwrap(),
+        "# version\nVERSION: str = '1.1.0.dev0'\n"
+    );
+
+    let dir = test_pyproject("hatch-path").await;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("example/__init__.py")).unwrap(),
+        "__version__ = \"1.1.0.dev0\"\n"
+    );
+}
@@ -1,15 +1,32 @@
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use regex::{Regex, Replacer};
 use serde::de::Error;
 use serde::Deserialize;
+use crate::utils;
 use crate::version_changer::VersionChanger;
 
 #[derive(Debug, Deserialize)]
 pub struct RegexPattern {
-    path: PathBuf,
+    /// the path of the file or glob pattern like `src/**/*.h`
+    path: String,
     #[serde(alias = "info", deserialize_with = "deserialize_regex")]
     pattern: Regex,
+    #[serde(default)]
+    replace: ReplaceMode,
+    /// the expected number of matches in each file
+    #[serde(default)]
+    matches: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum ReplaceMode {
+    /// only the first match in each file is used
+    #[default]
+    #[serde(rename = "first")]
+    First,
+    #[serde(rename = "all")]
+    All,
 }
 
 fn deserialize_regex<'de, D>(de: D) -> Result<Regex, D::Error> where D: serde::de::Deserializer<'de> {
@@ -43,36 +60,110 @@ fn str_to_regex(s: &str) -> Result<Regex, regex::Error> {
 
 impl Display for RegexPattern {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "regex-pattern(at {} with {})", self.path.display(), self.pattern)
+        write!(f, "regex-pattern(at {} with {})", self.path, self.pattern)
+    }
+}
+
+impl RegexPattern {
+    async fn read_files(&self) -> Vec<(PathBuf, String)> {
+        let paths = utils::glob::expand_path(&self.path);
+        if paths.is_empty() {
+            panic!("no files matched with {}", self.path);
+        }
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let content = tokio::fs::read_to_string(&path)
+                .await
+                .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+            files.push((path, content));
+        }
+        files
+    }
+
+    /// Returns the versions matched in the file, checking the number of matches
+    fn find_versions<'a>(&self, path: &Path, content: &'a str) -> Vec<&'a str> {
+        let versions = self.pattern.captures_iter(content)
+            .map(|captures| captures.name("version").unwrap().as_str())
+            .collect::<Vec<_>>();
+        if versions.is_empty() {
+            panic!("{} is not matched with the regex {}", path.display(), self.pattern);
+        }
+        if let Some(expected) = self.matches {
+            if versions.len() != expected {
+                panic!("expected {} matches in {} but found {}", expected, path.display(), versions.len());
+            }
+        }
+        versions
     }
+
+    fn limit(&self) -> usize {
+        match self.replace {
+            ReplaceMode::First => 1,
+            ReplaceMode::All => 0,
+        }
+    }
+}
+
+/// Splits options like `{all}` or `{first,2}` from the pattern in the changer string.
+/// A regex cannot start with `{` so the options never collide with the pattern
+fn split_options(info: &str) -> (ReplaceMode, Option<usize>, &str) {
+    let Some((options, pattern)) = info.strip_prefix('{').and_then(|x| x.split_once('}')) else {
+        return (ReplaceMode::First, None, info);
+    };
+    let (replace, matches) = match options.split_once(',') {
+        Some((replace, matches)) => (replace, Some(matches.parse().unwrap_or_else(|_| panic!("invalid match count for regex pattern: {matches}")))),
+        None => (options, None),
+    };
+    let replace = match replace {
+        "first" => ReplaceMode::First,
+        "all" => ReplaceMode::All,
+        _ => panic!("invalid replace mode for regex pattern: {replace}"),
+    };
+    (replace, matches, pattern)
 }
 
 impl VersionChanger for RegexPattern {
+    /// `regex-pattern:[{first|all[,matches]}]pattern@path`
     fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let (replace, matches, pattern) = split_options(info.expect("regex pattern needs pattern"));
         Self {
             path: path.expect("regex pattern needs path").into(),
-            pattern: str_to_regex(info.expect("regex pattern needs pattern"))
+            pattern: str_to_regex(pattern)
                 .expect("invalid pattern for regex pattern"),
+            replace,
+            matches,
         }
     }
 
     async fn load_version(&self) -> String {
-        let content = tokio::fs::read_to_string(&self.path)
-            .await
-            .expect("reading file");
-        let captures = self.pattern.captures(&content)
-            .expect("not matched with the regex");
-        captures.name("version").unwrap().as_str().to_string()
+        let mut found = Vec::new();
+        for (path, content) in self.read_files().await {
+            let mut versions = self.find_versions(&path, &content);
+            // only the first match is replaced so the later ones may have other versions
+            if self.replace == ReplaceMode::First {
+                versions.truncate(1);
+            }
+            found.extend(versions.into_iter().map(|version| (version.to_owned(), path.clone())));
+        }
+        let (version, first_path) = &found[0];
+        if let Some((other, path)) = found.iter().find(|(x, _)| x != version) {
+            panic!("version mismatch: {} in {} but {} in {}", version, first_path.display(), other, path.display());
+        }
+        version.clone()
     }
 
     async fn set_version(&self, version: &str) {
-        let content = tokio::fs::read_to_string(&self.path)
-            .await
-            .expect("reading file");
-        let new_content = self.pattern.replace(&content, SetVersion(version));
-        tokio::fs::write(&self.path, new_content.as_bytes())
-            .await
-            .expect("writing file");
+        let files = self.read_files().await;
+        // check all files before writing any
+        for (path, content) in &files {
+            self.find_versions(path, content);
+        }
+        for (path, content) in &files {
+            let new_content = self.pattern.replacen(content, self.limit(), SetVersion(version));
+            tokio::fs::write(path, new_content.as_bytes())
+                .await
+                .unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+        }
     }
 }
 
@@ -85,3 +176,86 @@ impl Replacer for SetVersion<'_> {
         dst.push_str(&caps["suffix"]);
     }
 }
+
+#[tokio::test]
+async fn replace_all_in_glob() {
+    let dir = super::copy_fixture("regex-pattern");
+    let changer = RegexPattern {
+        path: format!("{}/include/**/*.h", dir.path().display()),
+        pattern: str_to_regex("VERSION \"$1\"").unwrap(),
+        replace: ReplaceMode::All,
+        matches: None,
+    };
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0").await;
+    assert_eq!(changer.load_version().await, "1.1.0");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("include/a.h")).unwrap(),
+        "#define VERSION \"1.1.0\"\n// VERSION \"1.1.0\"\n"
+    );
+}
+
+#[tokio::test]
+async fn replace_first() {
+    let dir = super::copy_fixture("regex-pattern");
+    let changer = RegexPattern::parse(Some("version: $1\n"), dir.path().join("first.txt").to_str());
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0").await;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("first.txt")).unwrap(),
+        "version: 1.1.0\nrequires: 1.0.0\n"
+    );
+
+    let changer = RegexPattern::parse(Some("version: $1\n"), dir.path().join("twice.txt").to_str());
+    assert_eq!(changer.load_version().await, "1.0.0");
+    changer.set_version("1.1.0").await;
+    assert_eq!(changer.load_version().await, "1.1.0");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("twice.txt")).unwrap(),
+        "version: 1.1.0\nversion: 1.0.0\n"
+    );
+}
+
+#[test]
+fn options_in_changer_string() {
+    let changer = RegexPattern::parse(Some("{all,2}version: $1\n"), Some("a.txt"));
+    assert_eq!(changer.replace, ReplaceMode::All);
+    assert_eq!(changer.matches, Some(2));
+    assert_eq!(changer.pattern.as_str(), "(?<prefix>version: )(?<version>.*)(?<suffix>\n)");
+
+    let changer = RegexPattern::parse(Some("{first}version: $1\n"), Some("a.txt"));
+    assert_eq!(changer.replace, ReplaceMode::First);
+    assert_eq!(changer.matches, None);
+
+    // patterns looking like the old options are kept as is
+    let changer = RegexPattern::parse(Some("all: \"$1\""), Some("a.txt"));
+    assert_eq!(changer.replace, ReplaceMode::First);
+    assert_eq!(changer.matches, None);
+    assert_eq!(changer.pattern.as_str(), "(?<prefix>all: \")(?<version>.*)(?<suffix>\")");
+}
+
+#[tokio::test]
+#[should_panic(expected = "version mismatch")]
+async fn mismatch() {
+    let dir = super::copy_fixture("regex-pattern");
+    let changer = RegexPattern {
+        path: dir.path().join("mismatch.txt").display().to_string(),
+        pattern: str_to_regex("version: $1\n").unwrap(),
+        replace: ReplaceMode::All,
+        matches: None,
+    };
+    changer.load_version().await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "expected 1 matches")]
+async fn unexpected_matches() {
+    let dir = super::copy_fixture("regex-pattern");
+    let changer = RegexPattern {
+        path: dir.path().join("twice.txt").display().to_string(),
+        pattern: str_to_regex("version: $1\n").unwrap(),
+        replace: ReplaceMode::All,
+        matches: Some(1),
+    };
+    changer.set_version("1.1.0").await;
+}
@@ -0,0 +1,186 @@
+use crate::utils;
+use crate::version_changer::debian_changelog::{from_tilde_version, to_tilde_version};
+use crate::version_changer::VersionChanger;
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Sets `Version:` and `Release:` in the preamble of the RPM spec file.
+/// If `Version:` refers a macro, the `%global` or `%define` of the macro is updated.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RpmSpec {
+    /// path to the spec file. if not specified, the only `*.spec` file is used
+    path: Option<PathBuf>,
+    #[serde(default, alias = "info")]
+    release: ReleaseMode,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum ReleaseMode {
+    /// sets the number at the start of `Release:` to 1, keeping suffix like `%{?dist}`
+    #[default]
+    #[serde(rename = "reset")]
+    Reset,
+    #[serde(rename = "keep")]
+    Keep,
+}
+
+impl Display for RpmSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "rpm-spec(at {})", path.display()),
+            None => write!(f, "rpm-spec"),
+        }
+    }
+}
+
+impl RpmSpec {
+    fn path(&self) -> PathBuf {
+        if let Some(path) = &self.path {
+            return path.clone();
+        }
+        let mut found = utils::glob::expand_path("*.spec");
+        if found.len() != 1 {
+            panic!("rpm-spec needs path: {} spec files found", found.len());
+        }
+        found.remove(0)
+    }
+}
+
+impl VersionChanger for RpmSpec {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let release = match info {
+            None | Some("reset") => ReleaseMode::Reset,
+            Some("keep") => ReleaseMode::Keep,
+            Some(info) => panic!("invalid rpm-spec version changer: unknown release mode {info}"),
+        };
+        Self {
+            path: path.map(Into::into),
+            release,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let path = self.path();
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+        let version = find_version(&source).unwrap_or_else(|e| panic!("{e} in {}", path.display()));
+        from_tilde_version(&source[version])
+    }
+
+    async fn set_version(&self, version: &str) {
+        let path = self.path();
+        let mut source = tokio::fs::read_to_string(&path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+
+        let mut replacements = vec![(
+            find_version(&source).unwrap_or_else(|e| panic!("{e} in {}", path.display())),
+            to_tilde_version(version),
+        )];
+        if self.release == ReleaseMode::Reset {
+            let release = find_tag(&source, "Release")
+                .unwrap_or_else(|| panic!("no Release in {}", path.display()));
+            let number = source[release.clone()]
+                .bytes()
+                .take_while(u8::is_ascii_digit)
+                .count();
+            if number == 0 {
+                panic!(
+                    "Release in {} does not start with number: {}",
+                    path.display(),
+                    &source[release]
+                );
+            }
+            replacements.push((release.start..release.start + number, "1".to_owned()));
+        }
+
+        // replace later one first to keep the range of the other
+        replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        for (range, replacement) in replacements {
+            source.replace_range(range, &replacement);
+        }
+        tokio::fs::write(&path, source)
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+    }
+}
+
+/// Returns the range of the value of the first tag in the spec
+fn find_tag(source: &str, tag: &str) -> Option<Range<usize>> {
+    let regex = Regex::new(&format!(r"(?mi)^{tag}:[ \t]*(\S(?:.*\S)?)[ \t]*\r?$")).unwrap();
+    regex
+        .captures(source)
+        .map(|captures| captures.get(1).unwrap().range())
+}
+
+/// Returns the range of the version literal, following the macro definition if `Version:` is a macro
+fn find_version(source: &str) -> Result<Range<usize>, String> {
+    let version = find_tag(source, "Version").ok_or("no Version")?;
+    let value = &source[version.clone()];
+    if !value.contains('%') {
+        return Ok(version);
+    }
+
+    let macro_name = value
+        .strip_prefix("%{")
+        .and_then(|x| x.strip_suffix('}'))
+        .or_else(|| value.strip_prefix('%'))
+        .filter(|x| x.bytes().all(|x| x.is_ascii_alphanumeric() || x == b'_'))
+        .ok_or_else(|| format!("Version is not a literal nor a macro: {value}"))?;
+    let regex = Regex::new(&format!(
+        r"(?m)^%(?:global|define)[ \t]+{}[ \t]+(\S+)[ \t]*\r?$",
+        regex::escape(macro_name)
+    ))
+    .unwrap();
+    let definition = regex
+        .captures(source)
+        .ok_or_else(|| format!("no %global or %define for {macro_name}"))?
+        .get(1)
+        .unwrap();
+    if definition.as_str().contains('%') {
+        return Err(format!(
+            "{macro_name} is not a literal: {}",
+            definition.as_str()
+        ));
+    }
+    Ok(definition.range())
+}
+
+#[tokio::test]
+async fn spec() {
+    let dir = super::copy_fixture("rpm-spec");
+    let path = dir.path().join("example.spec");
+    let source = std::fs::read_to_string(&path).unwrap();
+    let path = path.to_str();
+
+    let changer = RpmSpec::parse(None, path);
+    assert_eq!(changer.load_version().await, "1.1.0");
+    changer.set_version("1.2.0-rc.1").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        source
+            .replace("Version:        1.1.0", "Version:        1.2.0~rc.1")
+            .replace("3%{?dist}", "1%{?dist}")
+    );
+    assert_eq!(changer.load_version().await, "1.2.0-rc.1");
+}
+
+#[tokio::test]
+async fn spec_macro() {
+    let dir = super::copy_fixture("rpm-spec/macro");
+    let path = dir.path().join("example.spec");
+    let source = std::fs::read_to_string(&path).unwrap();
+    let path = path.to_str();
+
+    let changer = RpmSpec::parse(Some("keep"), path);
+    assert_eq!(changer.load_version().await, "2.0.0");
+    changer.set_version("2.1.0").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        source.replace("2.0.0", "2.1.0")
+    );
+}
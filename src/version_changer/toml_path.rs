@@ -0,0 +1,139 @@
+use crate::utils;
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use toml_edit::{DocumentMut, Item, Key};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct TomlPath {
+    path: PathBuf,
+    /// dotted key path like `tool.mytool.version`. numeric keys are indices for arrays
+    #[serde(alias = "info")]
+    key: String,
+}
+
+impl Display for TomlPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "toml-path(at {} key {})", self.path.display(), self.key)
+    }
+}
+
+fn parse_key(key: &str) -> Vec<String> {
+    Key::parse(key)
+        .unwrap_or_else(|e| panic!("invalid key path {key}: {e}"))
+        .iter()
+        .map(|x| x.get().to_owned())
+        .collect()
+}
+
+impl TomlPath {
+    async fn read(&self) -> DocumentMut {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", self.path.display(), e))
+            .parse()
+            .unwrap_or_else(|e| panic!("parsing {}: {}", self.path.display(), e))
+    }
+
+    fn version_value<'a>(&self, document: &'a mut DocumentMut) -> &'a mut toml_edit::Value {
+        let mut item = document.as_item_mut();
+        for key in parse_key(&self.key) {
+            // get_mut with str inserts missing keys so check existence first
+            let index = key.parse::<usize>().ok().filter(|_| is_array(item));
+            let next = match index {
+                Some(index) => item.get_mut(index),
+                None if item.get(&key).is_some() => item.get_mut(&key),
+                None => None,
+            };
+            item = next.unwrap_or_else(|| panic!("no {} in {}", self.key, self.path.display()));
+        }
+        let type_name = item.type_name();
+        item.as_value_mut()
+            .filter(|x| x.is_str())
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} in {} is {}, not a string",
+                    self.key,
+                    self.path.display(),
+                    type_name
+                )
+            })
+    }
+}
+
+fn is_array(item: &Item) -> bool {
+    item.is_array() || item.is_array_of_tables()
+}
+
+impl VersionChanger for TomlPath {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let key = info.expect("toml-path needs key path");
+        parse_key(key);
+        Self {
+            path: path.expect("toml-path needs path").into(),
+            key: key.to_owned(),
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let mut document = self.read().await;
+        self.version_value(&mut document)
+            .as_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    async fn set_version(&self, version: &str) {
+        let mut document = self.read().await;
+        utils::toml::set_string(self.version_value(&mut document), version);
+        tokio::fs::write(&self.path, document.to_string())
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+#[tokio::test]
+async fn toml_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let source = r#"# configuration
+[tool.mytool]
+version = "1.0.0" # the version
+
+[tool."my.tool"]
+version = '1.0.0'
+
+[[mods]]
+modId = "example"
+version = "1.0.0"
+"#;
+    std::fs::write(&path, source).unwrap();
+    let path = path.to_str().unwrap();
+
+    for key in [
+        "tool.mytool.version",
+        r#"tool."my.tool".version"#,
+        "mods.0.version",
+    ] {
+        let changer = TomlPath::parse(Some(key), Some(path));
+        assert_eq!(changer.load_version().await, "1.0.0");
+        changer.set_version("1.1.0").await;
+        assert_eq!(changer.load_version().await, "1.1.0");
+    }
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        source.replace("1.0.0", "1.1.0")
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "is table, not a string")]
+async fn non_string() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[tool.mytool]\nversion = 1\n").unwrap();
+    TomlPath::parse(Some("tool.mytool"), Some(path.to_str().unwrap()))
+        .load_version()
+        .await;
+}
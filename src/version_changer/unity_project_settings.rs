@@ -0,0 +1,226 @@
+use crate::utils::yaml::{YamlDocument, YamlNode};
+use crate::version_changer::android::eval_formula;
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct UnityProjectSettings {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    /// how `AndroidBundleVersionCode` and `buildNumber` for each platform are updated
+    #[serde(default, alias = "info")]
+    build_code: BuildCode,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum BuildCode {
+    #[default]
+    #[serde(rename = "keep")]
+    Keep,
+    /// adds one to the current build codes
+    #[serde(rename = "increment")]
+    Increment,
+    /// `major * 10000 + minor * 100 + patch`. prerelease and build metadata are ignored
+    #[serde(rename = "derive")]
+    Derive,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("ProjectSettings/ProjectSettings.asset")
+}
+
+impl Display for UnityProjectSettings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unity-project-settings(at {})", self.path.display())
+    }
+}
+
+impl UnityProjectSettings {
+    async fn read(&self) -> String {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", self.path.display(), e))
+    }
+
+    fn player_settings<'a>(&self, document: &'a YamlDocument) -> &'a YamlNode {
+        document
+            .documents()
+            .iter()
+            .find_map(|x| x.get("PlayerSettings"))
+            .unwrap_or_else(|| panic!("no PlayerSettings in {}", self.path.display()))
+    }
+
+    fn scalar(&self, node: Option<&YamlNode>, name: &str) -> Range<usize> {
+        node.and_then(YamlNode::as_scalar)
+            .unwrap_or_else(|| panic!("no {} in {}", name, self.path.display()))
+    }
+}
+
+impl VersionChanger for UnityProjectSettings {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let build_code = match info {
+            None | Some("keep") => BuildCode::Keep,
+            Some("increment") => BuildCode::Increment,
+            Some("derive") => BuildCode::Derive,
+            Some(info) => panic!(
+                "invalid unity-project-settings version changer: unknown build code mode {info}"
+            ),
+        };
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            build_code,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = self.read().await;
+        let document = YamlDocument::parse(&source)
+            .unwrap_or_else(|e| panic!("parsing {}: {}", self.path.display(), e));
+        let settings = self.player_settings(&document);
+        document.scalar(self.scalar(settings.get("bundleVersion"), "bundleVersion"))
+    }
+
+    async fn set_version(&self, version: &str) {
+        let source = self.read().await;
+        let mut document = YamlDocument::parse(&source)
+            .unwrap_or_else(|e| panic!("parsing {}: {}", self.path.display(), e));
+        let settings = self.player_settings(&document);
+        let bundle_version = self.scalar(settings.get("bundleVersion"), "bundleVersion");
+
+        // build codes are looked up only if they are changed
+        let mut codes = Vec::new();
+        if self.build_code != BuildCode::Keep {
+            codes.push((
+                "AndroidBundleVersionCode".to_owned(),
+                self.scalar(
+                    settings.get("AndroidBundleVersionCode"),
+                    "AndroidBundleVersionCode",
+                ),
+            ));
+            if let Some(YamlNode::Mapping(platforms)) = settings.get("buildNumber") {
+                for (platform, node) in platforms {
+                    let name = format!("buildNumber.{platform}");
+                    codes.push((name.clone(), self.scalar(Some(node), &name)));
+                }
+            }
+        }
+
+        document.set_plain_scalar(bundle_version, version);
+        for (name, range) in codes {
+            let code = match self.build_code {
+                BuildCode::Keep => unreachable!(),
+                BuildCode::Increment => {
+                    let current = document.scalar(range.clone());
+                    let current = current.parse::<u32>().unwrap_or_else(|_| {
+                        panic!(
+                            "{name} in {} is not an integer: {current}",
+                            self.path.display()
+                        )
+                    });
+                    current + 1
+                }
+                BuildCode::Derive => derive_build_code(version),
+            };
+            document.set_integer(range, code);
+        }
+
+        tokio::fs::write(&self.path, document.to_string())
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+fn derive_build_code(version: &str) -> u32 {
+    eval_formula("major*10000 + minor*100 + patch", version)
+}
+
+#[test]
+fn derive_build_code_test() {
+    assert_eq!(derive_build_code("1.2.3"), 10203);
+    assert_eq!(derive_build_code("1.2"), 10200);
+    assert_eq!(derive_build_code("2.0.1-beta.1"), 20001);
+}
+
+#[cfg(test)]
+const PROJECT_SETTINGS: &str = r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!129 &1
+PlayerSettings:
+  m_ObjectHideFlags: 0
+  serializedVersion: 26
+  productGUID: 3f1c0d5e2b6a4c8f9e7d1a2b3c4d5e6f
+  companyName: DefaultCompany
+  productName: Example
+  defaultCursor: {fileID: 0}
+  m_SplashScreenLogos: []
+  bundleVersion: 1.0.0
+  preloadedAssets: []
+  buildNumber:
+    Standalone: 0
+    iPhone: 4
+    tvOS: 0
+  AndroidBundleVersionCode: 4
+  AndroidMinSdkVersion: 22
+  m_BuildTargetBatching:
+  - m_BuildTarget: Standalone
+    m_StaticBatching: 1
+  cloudProjectId:
+  webGLTemplate: APPLICATION:Default
+"#;
+
+#[tokio::test]
+async fn project_settings() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ProjectSettings.asset");
+    std::fs::write(&path, PROJECT_SETTINGS).unwrap();
+    let path = path.to_str();
+
+    let keep = UnityProjectSettings::parse(None, path);
+    assert_eq!(keep.load_version().await, "1.0.0");
+    keep.set_version("1.0.1").await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        PROJECT_SETTINGS.replace("bundleVersion: 1.0.0", "bundleVersion: 1.0.1")
+    );
+
+    UnityProjectSettings::parse(Some("increment"), path)
+        .set_version("1.1.0")
+        .await;
+    assert_eq!(
+        std::fs::read_to_string(path.unwrap()).unwrap(),
+        PROJECT_SETTINGS
+            .replace("bundleVersion: 1.0.0", "bundleVersion: 1.1.0")
+            .replace("Standalone: 0\n", "Standalone: 1\n")
+            .replace("iPhone: 4", "iPhone: 5")
+            .replace("tvOS: 0", "tvOS: 1")
+            .replace("AndroidBundleVersionCode: 4", "AndroidBundleVersionCode: 5")
+    );
+
+    UnityProjectSettings::parse(Some("derive"), path)
+        .set_version("1.2.0")
+        .await;
+    let content = std::fs::read_to_string(path.unwrap()).unwrap();
+    assert!(content.contains("  bundleVersion: 1.2.0\n"));
+    assert!(content.contains("    iPhone: 10200\n"));
+    assert!(content.contains("  AndroidBundleVersionCode: 10200\n"));
+    assert!(content.contains("  - m_BuildTarget: Standalone\n"));
+}
+
+#[tokio::test]
+async fn keep_without_build_codes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ProjectSettings.asset");
+    let source = PROJECT_SETTINGS.replace("  AndroidBundleVersionCode: 4\n", "");
+    std::fs::write(&path, &source).unwrap();
+    UnityProjectSettings::parse(None, path.to_str())
+        .set_version("1.10")
+        .await;
+    // unity writes version string as plain scalar even if it looks like a number
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        source.replace("bundleVersion: 1.0.0", "bundleVersion: 1.10")
+    );
+}
@@ -0,0 +1,127 @@
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct VersionFile {
+    #[serde(default = "path_default")]
+    path: PathBuf,
+    /// whether the file ends with newline after setting version
+    #[serde(default)]
+    newline: NewlinePolicy,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq)]
+enum NewlinePolicy {
+    /// keeps the trailing newline of the current file
+    #[default]
+    #[serde(rename = "keep")]
+    Keep,
+    #[serde(rename = "always")]
+    Always,
+    #[serde(rename = "never")]
+    Never,
+}
+
+fn path_default() -> PathBuf {
+    PathBuf::from("VERSION")
+}
+
+impl Display for VersionFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "version-file(at {})", self.path.display())
+    }
+}
+
+impl VersionFile {
+    async fn read(&self) -> Option<String> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => panic!("reading {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+impl VersionChanger for VersionFile {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let newline = match info {
+            None | Some("keep") => NewlinePolicy::Keep,
+            Some("always") => NewlinePolicy::Always,
+            Some("never") => NewlinePolicy::Never,
+            Some(info) => {
+                panic!("invalid version-file version changer: unknown newline policy {info}")
+            }
+        };
+        Self {
+            path: path.map(Into::into).unwrap_or_else(path_default),
+            newline,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let content = self
+            .read()
+            .await
+            .unwrap_or_else(|| panic!("{} not found", self.path.display()));
+        let version = content.trim();
+        if version.contains('\n') {
+            panic!("{} has multiple lines", self.path.display());
+        }
+        version.to_owned()
+    }
+
+    async fn set_version(&self, version: &str) {
+        if version.contains(['\n', '\r']) {
+            panic!("version cannot contain newline");
+        }
+        let current = self.read().await;
+        let newline = current
+            .as_deref()
+            .map(|x| if x.contains("\r\n") { "\r\n" } else { "\n" })
+            .unwrap_or("\n");
+        let has_newline = match self.newline {
+            // new file is created with newline
+            NewlinePolicy::Keep => current.as_deref().is_none_or(|x| x.ends_with('\n')),
+            NewlinePolicy::Always => true,
+            NewlinePolicy::Never => false,
+        };
+
+        let content = if has_newline {
+            format!("{version}{newline}")
+        } else {
+            version.to_owned()
+        };
+        tokio::fs::write(&self.path, content)
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+#[tokio::test]
+async fn version_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("VERSION");
+    let path_str = path.to_str().unwrap();
+
+    std::fs::write(&path, "1.0.0\r\n").unwrap();
+    let keep = VersionFile::parse(None, Some(path_str));
+    assert_eq!(keep.load_version().await, "1.0.0");
+    keep.set_version("1.1.0").await;
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1.1.0\r\n");
+
+    std::fs::write(&path, "1.0.0").unwrap();
+    keep.set_version("1.1.0").await;
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1.1.0");
+
+    VersionFile::parse(Some("always"), Some(path_str))
+        .set_version("1.2.0")
+        .await;
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1.2.0\n");
+
+    VersionFile::parse(Some("never"), Some(path_str))
+        .set_version("1.3.0")
+        .await;
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1.3.0");
+}
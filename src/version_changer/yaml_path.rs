@@ -0,0 +1,159 @@
+use crate::utils::yaml::YamlDocument;
+use crate::version_changer::VersionChanger;
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct YamlPath {
+    path: PathBuf,
+    /// dotted key path like `spec.template.metadata.labels.version`.
+    /// keys with dots can be quoted like `labels."app.kubernetes.io/version"`,
+    /// and numeric keys are indices for sequences.
+    #[serde(alias = "info")]
+    key: String,
+    /// the index of the document in multi-document yaml
+    #[serde(default)]
+    document: usize,
+}
+
+impl Display for YamlPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "yaml-path(at {} key {})", self.path.display(), self.key)
+    }
+}
+
+fn parse_key(key: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut rest = key;
+    loop {
+        let (segment, after) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .unwrap_or_else(|| panic!("unclosed quote in key path {key}"));
+            (&quoted[..end], &quoted[end + 1..])
+        } else {
+            let end = rest.find('.').unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        if segment.is_empty() {
+            panic!("empty key in key path {key}");
+        }
+        segments.push(segment.to_owned());
+        match after.strip_prefix('.') {
+            Some(after) => rest = after,
+            None if after.is_empty() => return segments,
+            None => panic!("invalid key path {key}"),
+        }
+    }
+}
+
+impl YamlPath {
+    async fn read(&self) -> String {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .unwrap_or_else(|e| panic!("reading {}: {}", self.path.display(), e))
+    }
+
+    fn version_scalar(&self, document: &YamlDocument) -> Range<usize> {
+        let root = document
+            .documents()
+            .get(self.document)
+            .unwrap_or_else(|| panic!("no document #{} in {}", self.document, self.path.display()));
+        let key = parse_key(&self.key);
+        let key = key.iter().map(String::as_str).collect::<Vec<_>>();
+        root.find(&key)
+            .unwrap_or_else(|| panic!("no {} in {}", self.key, self.path.display()))
+            .as_scalar()
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} in {} is not a single line scalar",
+                    self.key,
+                    self.path.display()
+                )
+            })
+    }
+}
+
+impl VersionChanger for YamlPath {
+    fn parse(info: Option<&str>, path: Option<&str>) -> Self {
+        let key = info.expect("yaml-path needs key path");
+        parse_key(key);
+        Self {
+            path: path.expect("yaml-path needs path").into(),
+            key: key.to_owned(),
+            document: 0,
+        }
+    }
+
+    async fn load_version(&self) -> String {
+        let source = self.read().await;
+        let document = YamlDocument::parse(&source)
+            .unwrap_or_else(|e| panic!("parsing {}: {}", self.path.display(), e));
+        document.scalar(self.version_scalar(&document))
+    }
+
+    async fn set_version(&self, version: &str) {
+        let source = self.read().await;
+        let mut document = YamlDocument::parse(&source)
+            .unwrap_or_else(|e| panic!("parsing {}: {}", self.path.display(), e));
+        document.set_scalar(self.version_scalar(&document), version);
+        tokio::fs::write(&self.path, document.to_string())
+            .await
+            .unwrap_or_else(|e| panic!("writing {}: {}", self.path.display(), e));
+    }
+}
+
+#[test]
+fn parse_key_test() {
+    assert_eq!(parse_key("version"), ["version"]);
+    assert_eq!(
+        parse_key(r#"metadata.labels."app.kubernetes.io/version""#),
+        ["metadata", "labels", "app.kubernetes.io/version"]
+    );
+    assert_eq!(
+        parse_key("containers.0.image"),
+        ["containers", "0", "image"]
+    );
+}
+
+#[tokio::test]
+async fn kubernetes_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("deployment.yaml");
+    let source = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: example
+  labels: &labels
+    app.kubernetes.io/version: "1.0.0" # managed by release
+spec:
+  template:
+    metadata:
+      labels:
+        version: 1.0.0
+    spec:
+      containers:
+        - name: example
+          image: example:1.0.0
+"#;
+    std::fs::write(&path, source).unwrap();
+    let path = path.to_str().unwrap();
+
+    for key in [
+        r#"metadata.labels."app.kubernetes.io/version""#,
+        "spec.template.metadata.labels.version",
+    ] {
+        let changer = YamlPath::parse(Some(key), Some(path));
+        assert_eq!(changer.load_version().await, "1.0.0");
+        changer.set_version("1.1.0").await;
+        assert_eq!(changer.load_version().await, "1.1.0");
+    }
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        source
+            .replace("\"1.0.0\"", "\"1.1.0\"")
+            .replace("version: 1.0.0", "version: 1.1.0")
+    );
+}